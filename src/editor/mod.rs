@@ -9,6 +9,7 @@ use crate::error;
 use crate::text::{self, Text, Pos};
 use crate::text::action::{Action, Movement, Operation};
 use crate::options;
+use crate::rdl;
 
 enum Mode {
   Normal,
@@ -39,6 +40,10 @@ impl Editor {
     self.text.set_text(text)
   }
   
+  pub fn set_rates(&mut self, rates: rdl::currency::Rates) {
+    self.writer.set_rates(rates)
+  }
+  
   pub fn key(&mut self) -> crossterm::Result<bool> {
     let evt = self.reader.read_key()?;
     let op = match self.mode {
@@ -17,6 +17,7 @@ use crate::frame::Frame;
 
 use crate::rdl;
 use crate::rdl::exec;
+use crate::rdl::currency;
 
 const _VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -25,6 +26,7 @@ pub struct Writer {
   term_size: (usize, usize),
   frame: Frame,
   buf: Buffer,
+  rates: currency::Rates,
 }
 
 impl Writer {
@@ -34,9 +36,14 @@ impl Writer {
       term_size: size,
       frame: Frame::new(size.0, opts),
       buf: Buffer::new(),
+      rates: currency::Rates::default(),
     }
   }
   
+  pub fn set_rates(&mut self, rates: currency::Rates) {
+    self.rates = rates;
+  }
+  
   pub fn clear() -> crossterm::Result<()> {
     execute!(stdout(), terminal::Clear(terminal::ClearType::All))?;
     execute!(stdout(), cursor::MoveTo(0, 0))?;
@@ -49,6 +56,7 @@ impl Writer {
     let mut fmla_text = String::new();
     let mut fmla_spns: Vec<attrs::Span> = Vec::new();
     let mut cxt = exec::Context::new_with_stdlib();
+    cxt.set_rates(self.rates.clone());
     
     let style = vec![
       attrs::Attributes{bold: true, invert: false, color: Some(Color::Yellow), background: None},
@@ -61,6 +69,7 @@ impl Writer {
     let opts = rdl::Options{
       verbose: self.opts.debug,
       debug: self.opts.debug,
+      packed: self.opts.packed,
     };
     
     let mut boff0 = 0;
@@ -2,10 +2,13 @@ use std::io;
 use std::fmt;
 use std::string;
 
+use crate::rdl;
+
 #[derive(Debug)]
 pub enum Error {
   IOError(io::Error),
   UTF8Error(string::FromUtf8Error),
+  Parse(rdl::error::Error),
 }
 
 impl From<io::Error> for Error {
@@ -20,11 +23,21 @@ impl From<string::FromUtf8Error> for Error {
   }
 }
 
+impl From<rdl::error::Error> for Error {
+  fn from(error: rdl::error::Error) -> Self {
+    match error {
+      rdl::error::Error::IOError(err) => Self::IOError(io::Error::other(err.to_string())),
+      error => Self::Parse(error),
+    }
+  }
+}
+
 impl fmt::Display for Error {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       Self::IOError(err) => err.fmt(f),
       Self::UTF8Error(err) => err.fmt(f),
+      Self::Parse(err) => err.fmt(f),
     }
   }
 }
@@ -53,12 +53,23 @@ impl Reader {
 
 fn main() -> Result<(), error::Error> {
   let opts = options::Options::parse();
+  let rates = match &opts.rates {
+    Some(path) => rdl::currency::Rates::read(path)?,
+    None => match rdl::currency::Rates::read_default() {
+      Ok(rates) => rates,
+      Err(err) => {
+        eprintln!("Using the built-in exchange rates: {}", err);
+        rdl::currency::Rates::default()
+      },
+    },
+  };
   let _cleanup = Finalize{opts: opts.clone()};
   execute!(stdout(), terminal::EnterAlternateScreen)?;
   terminal::enable_raw_mode()?;
   
   let size = terminal::size().unwrap();
   let mut editor = Editor::new_with_size((size.0 as usize, size.1 as usize), opts.clone());
+  editor.set_rates(rates);
   if let Some(doc) = opts.doc {
     match fs::read_to_string(doc) {
       Ok(text) => editor.set_text(text),
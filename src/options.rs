@@ -11,6 +11,10 @@ pub struct Options {
   pub debug_editor: bool,
   #[clap(long)]
   pub verbose: bool,
+  #[clap(long, help="Display results in mixed units, as in '5 ft 3 in'")]
+  pub packed: bool,
+  #[clap(long, help="Exchange rates to convert currencies with (default: ~/.resolver/rates)")]
+  pub rates: Option<String>,
   #[clap(help="Document to open")]
   pub doc: Option<String>,
 }
@@ -0,0 +1,186 @@
+use std::env;
+use std::fs;
+use std::path;
+use std::sync;
+
+use crate::rdl::unit;
+use crate::rdl::error;
+
+/// The exchange rates used when no rates file has been written yet. They
+/// are a snapshot and go stale, which is why the date is shown alongside
+/// every conversion.
+const BUILTIN: &str = "
+# units of each currency that one of the base currency buys
+date 2026-10-01
+base USD
+EUR 0.86
+GBP 0.75
+JPY 150.0
+CHF 0.80
+CAD 1.40
+AUD 1.53
+NZD 1.74
+CNY 7.12
+INR 88.7
+MXN 18.4
+BRL 5.40
+SEK 9.45
+NOK 10.0
+DKK 6.42
+PLN 3.66
+KRW 1420.0
+SGD 1.30
+HKD 7.78
+ZAR 17.3
+";
+
+/// Where the rates file is read from unless another is given, relative
+/// to the home directory.
+const RATES_PATH: &str = ".resolver/rates";
+
+/// The currency written as the symbol `c` before an amount, as in '$100'.
+pub fn symbol(c: char) -> Option<&'static str> {
+  match c {
+    '$' => Some("USD"),
+    '€' => Some("EUR"),
+    '£' => Some("GBP"),
+    '¥' => Some("JPY"),
+    _   => None,
+  }
+}
+
+/// Exchange rates, as the units of each currency that one of the base
+/// currency buys, and the date they were taken on. They are read from a
+/// file the user keeps up to date, so that no network is needed:
+///
+///   date 2026-10-01
+///   base USD
+///   EUR 0.86
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rates {
+  date: String,
+  base: String,
+  rates: Vec<(String, f64)>,
+}
+
+impl Rates {
+  pub fn parse(text: &str) -> Result<Rates, error::Error> {
+    let mut date: Option<String> = None;
+    let mut base: Option<String> = None;
+    let mut rates: Vec<(String, f64)> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+      let line = match line.split_once('#') {
+        Some((line, _)) => line,
+        None => line,
+      };
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      match fields.as_slice() {
+        [] => continue,
+        ["date", v] => date = Some(v.to_string()),
+        ["base", v] if Self::is_code(v) => base = Some(v.to_string()),
+        [code, v] if Self::is_code(code) => match v.parse::<f64>() {
+          Ok(rate) if rate > 0.0 => rates.push((code.to_string(), rate)),
+          _ => return Err(Self::syntax_error(line, n)),
+        },
+        _ => return Err(Self::syntax_error(line, n)),
+      };
+    }
+    match (date, base) {
+      (Some(date), Some(base)) => Ok(Rates{
+        date: date,
+        base: base,
+        rates: rates,
+      }),
+      _ => Err(error::SyntaxError::new(text, 0..text.len(), "Exchange rates need a date and a base currency").into()),
+    }
+  }
+  
+  /// Read exchange rates from the file at `path`.
+  pub fn read<P: AsRef<path::Path>>(path: P) -> Result<Rates, error::Error> {
+    match fs::read_to_string(path.as_ref()) {
+      Ok(text) => Rates::parse(&text),
+      Err(err) => Err(error::IOError::new(&format!("{}: {}", path.as_ref().display(), err)).into()),
+    }
+  }
+  
+  /// Read exchange rates from the rates file in the home directory, or
+  /// produce the built-in ones if there is no such file.
+  pub fn read_default() -> Result<Rates, error::Error> {
+    let path = match env::var_os("HOME") {
+      Some(home) => path::Path::new(&home).join(RATES_PATH),
+      None => return Ok(Rates::default()),
+    };
+    if path.exists() {
+      Rates::read(path)
+    }else{
+      Ok(Rates::default())
+    }
+  }
+  
+  pub fn date(&self) -> &str {
+    &self.date
+  }
+  
+  /// Declare every currency as a unit, replacing those declared by any
+  /// earlier rates.
+  pub fn declare(&self, units: &mut unit::Registry) {
+    units.clear(unit::Dimension::Money);
+    units.declare_unit(&self.base, 1.0, unit::Dimension::Money);
+    for (code, rate) in &self.rates {
+      units.declare_unit(code, 1.0 / rate, unit::Dimension::Money);
+    }
+  }
+  
+  fn is_code(name: &str) -> bool {
+    name.chars().all(|c| { c.is_alphabetic() })
+  }
+  
+  fn syntax_error(line: &str, n: usize) -> error::Error {
+    error::SyntaxError::new(line, 0..line.len(), &format!("Invalid exchange rate on line {}", n + 1)).into()
+  }
+}
+
+impl Default for Rates {
+  /// The built-in rates, which are only parsed once, however many
+  /// contexts are created.
+  fn default() -> Rates {
+    static RATES: sync::OnceLock<Rates> = sync::OnceLock::new();
+    RATES.get_or_init(|| { Rates::parse(BUILTIN).expect("Built-in exchange rates are invalid") }).clone()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  
+  #[test]
+  fn parse_rates() {
+    let rates = Rates::parse("date 2026-09-30\nbase EUR # the euro\n\nUSD 1.25\n").unwrap();
+    assert_eq!("2026-09-30", rates.date());
+    assert_eq!("EUR", rates.base);
+    assert_eq!(vec![("USD".to_string(), 1.25)], rates.rates);
+    
+    assert!(Rates::parse("base USD\nEUR 0.8").is_err()); // no date
+    assert!(Rates::parse("date 2026-09-30\nEUR 0.8").is_err()); // no base
+    assert!(Rates::parse("date 2026-09-30\nbase USD\nEUR lots").is_err());
+    assert!(Rates::parse("date 2026-09-30\nbase USD\nEUR -1").is_err());
+    assert!(Rates::parse("date 2026-09-30\nbase USD\nEUR 0.8 today").is_err());
+    
+    assert_eq!("2026-10-01", Rates::default().date());
+  }
+  
+  #[test]
+  fn declare_rates() {
+    let mut units = unit::Registry::default();
+    Rates::parse("date 2026-09-30\nbase USD\nEUR 0.8\nGBP 0.5").unwrap().declare(&mut units);
+    let eur = unit::Compound::from_with("EUR", &units).unwrap();
+    let gbp = unit::Compound::from_with("GBP", &units).unwrap();
+    assert!(eur.is_money());
+    assert_eq!(Some(unit::Value::option(50.0, Some(gbp.clone()))), unit::Value::option(80.0, Some(eur.clone())).convert_compound(Some(&gbp)));
+    
+    Rates::parse("date 2026-10-01\nbase EUR\nUSD 1.25").unwrap().declare(&mut units);
+    assert_eq!(None, unit::Compound::from_with("GBP", &units)); // no longer quoted
+    assert_eq!(Some(unit::Value::option(100.0, unit::Compound::from_with("EUR", &units))), unit::Value::option(125.0, unit::Compound::from_with("USD", &units)).convert_compound(unit::Compound::from_with("EUR", &units).as_ref()));
+  }
+}
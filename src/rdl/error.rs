@@ -1,7 +1,7 @@
 use std::fmt;
 use std::ops;
 use std::error;
-use std::num::ParseFloatError;
+use std::num::{ParseFloatError, ParseIntError};
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct IOError {
@@ -99,9 +99,16 @@ pub enum Error {
   TokenNotMatched,
   InvalidASTNode(String),
   UnboundVariable(String),
+  UnboundFunction(String),
+  InvalidArguments(String),
+  InvalidOperation(String),
+  UnitMismatch(String, String),
+  UnknownUnit(String),
+  NoValue,
   AssertionFailed(AssertionFailed),
   SyntaxError(SyntaxError),
   ParseFloatError(ParseFloatError),
+  ParseIntError(ParseIntError),
 }
 
 impl From<IOError> for Error {
@@ -128,6 +135,12 @@ impl From<ParseFloatError> for Error {
   }
 }
 
+impl From<ParseIntError> for Error {
+  fn from(error: ParseIntError) -> Self {
+    Self::ParseIntError(error)
+  }
+}
+
 impl fmt::Display for Error {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
@@ -136,9 +149,16 @@ impl fmt::Display for Error {
       Self::TokenNotMatched => write!(f, "Token not matched"),
       Self::InvalidASTNode(node) => write!(f, "Invalid AST node: {}", node),
       Self::UnboundVariable(name) => write!(f, "No such variable: {}", name),
+      Self::UnboundFunction(name) => write!(f, "No such function: {}", name),
+      Self::InvalidArguments(msg) => write!(f, "Invalid arguments: {}", msg),
+      Self::InvalidOperation(msg) => write!(f, "Invalid operation: {}", msg),
+      Self::UnitMismatch(left, right) => write!(f, "Incompatible units: {} and {}", left, right),
+      Self::UnknownUnit(name) => write!(f, "Unknown unit: {}", name),
+      Self::NoValue => write!(f, "Expression produces no value"),
       Self::AssertionFailed(err) => err.fmt(f),
       Self::SyntaxError(err) => err.fmt(f),
       Self::ParseFloatError(err) => err.fmt(f),
+      Self::ParseIntError(err) => err.fmt(f),
     }
   }
 }
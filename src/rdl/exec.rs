@@ -2,52 +2,257 @@ use std::fmt;
 use std::collections::HashMap;
 
 use crate::rdl::unit;
+use crate::rdl::ingredient;
+use crate::rdl::currency;
+use crate::rdl::value;
+use crate::rdl::func;
 use crate::rdl::error;
 
 pub struct Context {
-  vars: HashMap<String, unit::Value>,
+  vars: HashMap<String, value::Value>,
+  funcs: HashMap<String, func::Function>,
+  units: unit::Registry,
+  rates: currency::Rates,
+  exchanged: bool, // whether exchange rates were used since this was last checked
+  derived: bool, // whether the current line refers to earlier results
+  depth: usize,
+  lines: Vec<Option<value::Value>>, // the result of each line evaluated so far
+  block: Vec<value::Value>, // line results since the last blank line or heading
 }
 
 impl Context {
   pub fn new() -> Context {
     Context{
       vars: HashMap::new(),
+      funcs: HashMap::new(),
+      units: unit::Registry::default(),
+      rates: currency::Rates::default(),
+      exchanged: false,
+      derived: false,
+      depth: 0,
+      lines: Vec::new(),
+      block: Vec::new(),
     }
   }
   
   pub fn new_with_stdlib() -> Context {
     let mut vars = HashMap::new();
-    vars.insert("pi".to_string(), unit::Value::raw(std::f64::consts::PI));
-    vars.insert("tau".to_string(), unit::Value::raw(std::f64::consts::TAU));
-    vars.insert("E".to_string(), unit::Value::raw(std::f64::consts::E));
-    Context{
-      vars: vars,
+    vars.insert("pi".to_string(), value::Value::raw(std::f64::consts::PI));
+    vars.insert("tau".to_string(), value::Value::raw(std::f64::consts::TAU));
+    vars.insert("E".to_string(), value::Value::raw(std::f64::consts::E));
+    let constant = |v: f64, unit: &str| { value::Value::Number(unit::Value::option(v, unit::Compound::from(unit))) };
+    // a name on its own is read as a variable, so 'g * 80 kg' uses gravity
+    // while '80 g' is still in grams
+    vars.insert("g".to_string(), constant(9.80665, "m/s²"));         // standard gravity
+    vars.insert("c".to_string(), constant(299792458.0, "m/s"));      // speed of light
+    vars.insert("G".to_string(), constant(6.67430e-11, "m³/kg*s²")); // gravitational constant
+    let mut funcs = HashMap::new();
+    for f in func::stdlib() {
+      funcs.insert(f.name.to_string(), func::Function::Builtin(f));
     }
+    let mut cxt = Context{
+      vars: vars,
+      funcs: funcs,
+      units: unit::Registry::default(),
+      rates: currency::Rates::default(),
+      exchanged: false,
+      derived: false,
+      depth: 0,
+      lines: Vec::new(),
+      block: Vec::new(),
+    };
+    cxt.rates.declare(&mut cxt.units);
+    cxt
   }
   
-  pub fn set(&mut self, key: &str, val: unit::Value) {
+  pub fn set(&mut self, key: &str, val: value::Value) {
     self.vars.insert(key.to_string(), val);
   }
   
-  pub fn get(&self, key: &str) -> Option<unit::Value> {
+  pub fn get(&self, key: &str) -> Option<value::Value> {
     match self.vars.get(key) {
-      Some(v) => Some(*v),
+      Some(v) => Some(v.clone()),
+      None => None,
+    }
+  }
+  
+  /// The names of every variable that is set.
+  pub fn vars(&self) -> Vec<String> {
+    self.vars.keys().cloned().collect()
+  }
+  
+  pub fn unset(&mut self, key: &str) {
+    self.vars.remove(key);
+  }
+  
+  pub fn define(&mut self, key: &str, f: func::Function) {
+    self.funcs.insert(key.to_string(), f);
+  }
+  
+  pub fn func(&self, key: &str) -> Option<func::Function> {
+    match self.funcs.get(key) {
+      Some(f) => Some(f.clone()),
       None => None,
     }
   }
+  
+  /// Declare a unit for the rest of the document, as a multiple of the
+  /// quantity `def`, or as a base unit of its own if there is none.
+  pub fn declare(&mut self, name: &str, def: Option<&unit::Value>) {
+    match def {
+      Some(def) => self.units.declare(name, def),
+      None => self.units.declare_base(name),
+    }
+  }
+  
+  pub fn units(&self) -> &unit::Registry {
+    &self.units
+  }
+  
+  /// Convert currencies with the exchange rates `rates` from now on.
+  pub fn set_rates(&mut self, rates: currency::Rates) {
+    rates.declare(&mut self.units);
+    self.rates = rates;
+  }
+  
+  pub fn rates(&self) -> &currency::Rates {
+    &self.rates
+  }
+  
+  /// Whether an amount was converted from one currency to another since
+  /// this was last asked.
+  pub fn exchanged(&mut self) -> bool {
+    std::mem::replace(&mut self.exchanged, false)
+  }
+  
+  fn exchange(&mut self, from: Option<&unit::Compound>, to: Option<&unit::Compound>) {
+    if let (Some(from), Some(to)) = (from, to) {
+      let (from, to) = (from.money(), to.money()); // as in €/h in $/h
+      if !from.is_empty() && !to.is_empty() && from != to {
+        self.exchanged = true;
+      }
+    }
+  }
+  
+  /// Resolve a name to a value. Variables take precedence; otherwise the
+  /// name may refer to earlier results: 'prev' or 'ans' for the most recent
+  /// one, 'lineN' for the result of line N, and 'sum', 'total' or 'average'
+  /// for the results in the current block.
+  pub fn lookup(&mut self, key: &str) -> Result<value::Value, error::Error> {
+    if let Some(v) = self.get(key) {
+      return Ok(v);
+    }
+    let res = match key {
+      "prev" | "ans" => self.lines.iter().rev().find_map(|v| { v.clone() }),
+      "sum" | "total" => Some(self.total()?),
+      "average" => match self.numbers().count() {
+        0 => None,
+        n => Some((self.total()? / value::Value::raw(n as f64))?),
+      },
+      _ => match key.strip_prefix("line").map(|n| { n.parse::<usize>() }) {
+        Some(Ok(n)) if n > 0 => self.lines.get(n - 1).cloned().flatten(),
+        _ => None,
+      },
+    };
+    match res {
+      Some(v) => {
+        self.derived = true;
+        Ok(v)
+      },
+      None => Err(error::Error::UnboundVariable(key.to_owned())),
+    }
+  }
+  
+  fn numbers<'a>(&'a self) -> impl Iterator<Item = &'a value::Value> {
+    self.block.iter().filter(|v| { matches!(v, value::Value::Number(_)) })
+  }
+  
+  fn total(&self) -> Result<value::Value, error::Error> {
+    let mut sum = value::Value::raw(0.0);
+    for v in self.numbers() {
+      sum = (sum + v.clone())?;
+    }
+    Ok(sum)
+  }
+  
+  /// Record the result of a line once it has been evaluated. A line that
+  /// produces no result ends the current block if it is blank. A line that
+  /// refers to earlier results, as in 'sum' or 'prev', is not itself part
+  /// of the block, so that it is not counted twice.
+  pub fn end_line(&mut self, text: &str, res: Option<value::Value>) {
+    self.lines.push(res.clone());
+    let derived = std::mem::replace(&mut self.derived, false);
+    match res {
+      Some(_) if derived => {},
+      Some(v) => self.block.push(v),
+      None => if text.trim().is_empty() {
+        self.block.clear();
+      },
+    };
+  }
+  
+  /// Start a new block of lines for 'sum', 'total' and 'average'.
+  pub fn end_block(&mut self) {
+    self.block.clear();
+  }
+  
+  /// The number of calls to defined functions currently in progress.
+  pub fn depth(&self) -> usize {
+    self.depth
+  }
+  
+  pub fn descend(&mut self) {
+    self.depth += 1;
+  }
+  
+  pub fn ascend(&mut self) {
+    self.depth -= 1;
+  }
+}
+
+/// Wrap an error from executing a child node. A unit mismatch or an
+/// unknown unit is passed on as it is, so that it can be shown in place of
+/// the missing result.
+fn child_error<T: fmt::Display>(node: T, child: &str, err: error::Error) -> error::Error {
+  match err {
+    error::Error::UnitMismatch(..) | error::Error::UnknownUnit(..) => err,
+    err => error::Error::InvalidASTNode(format!("{}: Could not exec {}: {}", node, child, err)),
+  }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum NType {
   Ident,
   Number,
+  String,
   Assign,
   Typecast,
+  Ingredient,
   Add,
   Sub,
   Mul,
   Div,
   Mod,
+  Pow,
+  Percent,
+  Of,
+  PercentOf,
+  WholeOf,
+  Neg,
+  Pos,
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  And,
+  Or,
+  Not,
+  If,
+  Call,
+  Define,
+  Declare,
 }
 
 impl fmt::Display for NType {
@@ -55,24 +260,47 @@ impl fmt::Display for NType {
     match self {
       NType::Ident    => write!(f, "ident"),
       NType::Number   => write!(f, "value"),
+      NType::String   => write!(f, "string"),
       NType::Assign   => write!(f, "="),
       NType::Typecast => write!(f, ":"),
+      NType::Ingredient => write!(f, "ingredient"),
       NType::Add      => write!(f, "+"),
       NType::Sub      => write!(f, "-"),
       NType::Mul      => write!(f, "*"),
       NType::Div      => write!(f, "/"),
-      NType::Mod      => write!(f, "%"),
+      NType::Mod      => write!(f, "mod"),
+      NType::Pow      => write!(f, "^"),
+      NType::Percent  => write!(f, "%"),
+      NType::Of       => write!(f, "of"),
+      NType::PercentOf => write!(f, "as % of"),
+      NType::WholeOf  => write!(f, "is % of what"),
+      NType::Neg      => write!(f, "-"),
+      NType::Pos      => write!(f, "+"),
+      NType::Eq       => write!(f, "=="),
+      NType::Ne       => write!(f, "!="),
+      NType::Lt       => write!(f, "<"),
+      NType::Le       => write!(f, "<="),
+      NType::Gt       => write!(f, ">"),
+      NType::Ge       => write!(f, ">="),
+      NType::And      => write!(f, "and"),
+      NType::Or       => write!(f, "or"),
+      NType::Not      => write!(f, "not"),
+      NType::If       => write!(f, "if"),
+      NType::Call     => write!(f, "call"),
+      NType::Define   => write!(f, "define"),
+      NType::Declare  => write!(f, "unit"),
     }
   }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Node {
   ntype: NType,
   left:  Option<Box<Node>>,
   right: Option<Box<Node>>,
   text:  Option<String>,
   value: Option<f64>,
+  args:  Option<Vec<Node>>,
 }
 
 impl fmt::Display for Node {
@@ -91,6 +319,7 @@ impl Node {
       left: None, right: None,
       text: Some(name.to_string()),
       value: None,
+      args: None,
     }
   }
   
@@ -100,6 +329,17 @@ impl Node {
       left: None, right: None,
       text: None,
       value: Some(value),
+      args: None,
+    }
+  }
+  
+  pub fn new_string(text: &str) -> Node {
+    Node{
+      ntype: NType::String,
+      left: None, right: None,
+      text: Some(text.to_string()),
+      value: None,
+      args: None,
     }
   }
   
@@ -109,6 +349,7 @@ impl Node {
       left: Some(Box::new(left)), right: Some(Box::new(right)),
       text: Some("=".to_string()),
       value: None,
+      args: None,
     }
   }
   
@@ -118,6 +359,30 @@ impl Node {
       left: Some(Box::new(left)), right: Some(Box::new(right)),
       text: Some(":".to_string()),
       value: None,
+      args: None,
+    }
+  }
+  
+  /// A conversion asked for in an expression, as in '5 km in mi', which
+  /// also fixes the unit its result is displayed in.
+  pub fn new_convert(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Typecast,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("in".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  /// A quantity of an ingredient, as in '1 cup flour'.
+  pub fn new_ingredient(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Ingredient,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: None,
+      value: None,
+      args: None,
     }
   }
   
@@ -127,6 +392,7 @@ impl Node {
       left: Some(Box::new(left)), right: Some(Box::new(right)),
       text: Some("+".to_string()),
       value: None,
+      args: None,
     }
   }
   
@@ -136,6 +402,7 @@ impl Node {
       left: Some(Box::new(left)), right: Some(Box::new(right)),
       text: Some("-".to_string()),
       value: None,
+      args: None,
     }
   }
   
@@ -145,6 +412,7 @@ impl Node {
       left: Some(Box::new(left)), right: Some(Box::new(right)),
       text: Some("*".to_string()),
       value: None,
+      args: None,
     }
   }
   
@@ -154,6 +422,7 @@ impl Node {
       left: Some(Box::new(left)), right: Some(Box::new(right)),
       text: Some("/".to_string()),
       value: None,
+      args: None,
     }
   }
   
@@ -161,8 +430,267 @@ impl Node {
     Node{
       ntype: NType::Mod,
       left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("mod".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_pow(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Pow,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("^".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_percent(right: Node) -> Node {
+    Node{
+      ntype: NType::Percent,
+      left: None, right: Some(Box::new(right)),
       text: Some("%".to_string()),
       value: None,
+      args: None,
+    }
+  }
+  
+  /// A percentage of a base value, as in '20% of 150'.
+  pub fn new_of(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Of,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("of".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  /// A value expressed as a percentage of another, as in '30 as % of 120'.
+  pub fn new_percent_of(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::PercentOf,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("as % of".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  /// The whole that a value is a percentage of, as in '30 is 25% of what'.
+  pub fn new_whole_of(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::WholeOf,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("is % of what".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_neg(right: Node) -> Node {
+    Node{
+      ntype: NType::Neg,
+      left: None, right: Some(Box::new(right)),
+      text: Some("-".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_pos(right: Node) -> Node {
+    Node{
+      ntype: NType::Pos,
+      left: None, right: Some(Box::new(right)),
+      text: Some("+".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_eq(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Eq,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("==".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_ne(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Ne,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("!=".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_lt(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Lt,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("<".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_le(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Le,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("<=".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_gt(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Gt,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some(">".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_ge(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Ge,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some(">=".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_and(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::And,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("and".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_or(left: Node, right: Node) -> Node {
+    Node{
+      ntype: NType::Or,
+      left: Some(Box::new(left)), right: Some(Box::new(right)),
+      text: Some("or".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  pub fn new_not(right: Node) -> Node {
+    Node{
+      ntype: NType::Not,
+      left: None, right: Some(Box::new(right)),
+      text: Some("not".to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  /// A conditional; the condition and both branches are stored in order
+  /// as arguments.
+  pub fn new_if(cond: Node, then: Node, other: Node) -> Node {
+    Node{
+      ntype: NType::If,
+      left: None, right: None,
+      text: Some("if".to_string()),
+      value: None,
+      args: Some(vec![cond, then, other]),
+    }
+  }
+  
+  pub fn new_call(name: &str, args: Vec<Node>) -> Node {
+    Node{
+      ntype: NType::Call,
+      left: None, right: None,
+      text: Some(name.to_string()),
+      value: None,
+      args: Some(args),
+    }
+  }
+  
+  pub fn new_define(name: &str, params: Vec<Node>, body: Node) -> Node {
+    Node{
+      ntype: NType::Define,
+      left: None, right: Some(Box::new(body)),
+      text: Some(name.to_string()),
+      value: None,
+      args: Some(params),
+    }
+  }
+  
+  /// A unit declaration, as in 'unit sprint = 2 weeks'.
+  pub fn new_declare(name: &str, def: Node) -> Node {
+    Node{
+      ntype: NType::Declare,
+      left: None, right: Some(Box::new(def)),
+      text: Some(name.to_string()),
+      value: None,
+      args: None,
+    }
+  }
+  
+  /// The name of an identifier node, or None for any other node.
+  pub fn name<'a>(&'a self) -> Option<&'a str> {
+    match self.ntype {
+      NType::Ident => self.text.as_deref(),
+      _ => None,
+    }
+  }
+  
+  /// The display format an expression asks for, as in '63 in as packed',
+  /// or None if it leaves that to the document.
+  pub fn format(&self) -> Option<value::Format> {
+    match self.ntype {
+      NType::Typecast => self.right.as_ref().and_then(|r| { r.name() }).and_then(value::Format::from),
+      NType::Assign => self.right.as_ref().and_then(|r| { r.format() }),
+      _ => None,
+    }
+  }
+  
+  /// Whether an expression converts its result into a unit it names, as
+  /// in '5 km in mi', rather than leaving the unit to the calculation.
+  pub fn is_converted(&self) -> bool {
+    match self.ntype {
+      NType::Typecast if self.format().is_some() => self.left.as_ref().is_some_and(|l| { l.is_converted() }),
+      NType::Typecast => self.text.as_deref() == Some("in"),
+      NType::Assign => self.right.as_ref().is_some_and(|r| { r.is_converted() }),
+      _ => false,
+    }
+  }
+  
+  /// The name and arguments of a call node, or None for any other node.
+  pub fn call<'a>(&'a self) -> Option<(&'a str, &'a [Node])> {
+    match (self.ntype, &self.text, &self.args) {
+      (NType::Call, Some(name), Some(args)) => Some((name, args)),
+      _ => None,
+    }
+  }
+  
+  /// Whether this node is a percentage literal, as in '15%'.
+  pub fn is_percent(&self) -> bool {
+    self.ntype == NType::Percent
+  }
+  
+  /// Whether this node always produces a boolean when it is evaluated,
+  /// which is to say it is a comparison or a logical operation.
+  pub fn is_boolean(&self) -> bool {
+    match self.ntype {
+      NType::Eq | NType::Ne | NType::Lt | NType::Le | NType::Gt | NType::Ge => true,
+      NType::And | NType::Or | NType::Not => true,
+      _ => false,
     }
   }
   
@@ -173,13 +701,20 @@ impl Node {
     }
   }
   
-  fn value(&self) -> Result<unit::Value, error::Error> {
+  fn value(&self) -> Result<value::Value, error::Error> {
     match self.value {
-      Some(value) => Ok(unit::Value::raw(value)),
+      Some(value) => Ok(value::Value::raw(value)),
       None => Err(error::Error::InvalidASTNode(format!("{}: Expected value", self.ntype))),
     }
   }
   
+  fn args<'a>(&'a self) -> Result<&'a Vec<Node>, error::Error> {
+    match &self.args {
+      Some(args) => Ok(args),
+      None => Err(error::Error::InvalidASTNode(format!("{}: Expected arguments", self.ntype))),
+    }
+  }
+  
   fn left<'a>(&'a self) -> Result<&'a Box<Node>, error::Error> {
     match &self.left {
       Some(left) => Ok(left),
@@ -194,29 +729,41 @@ impl Node {
     }
   }
   
-  pub fn exec(&self, cxt: &mut Context) -> Result<unit::Value, error::Error> {
+  pub fn exec(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
     match self.ntype {
       NType::Ident    => self.exec_ident(cxt),
       NType::Number   => self.exec_number(cxt),
+      NType::String   => self.exec_string(cxt),
       NType::Assign   => self.exec_assign(cxt),
       NType::Typecast => self.exec_typecast(cxt),
-      NType::Add | NType::Sub | NType::Mul | NType::Div | NType::Mod => self.exec_arith(cxt),
+      NType::Ingredient => self.exec_ingredient(cxt),
+      NType::Add | NType::Sub | NType::Mul | NType::Div | NType::Mod | NType::Pow => self.exec_arith(cxt),
+      NType::Of | NType::PercentOf | NType::WholeOf => self.exec_arith(cxt),
+      NType::Neg | NType::Pos => self.exec_unary(cxt),
+      NType::Percent  => self.exec_percent(cxt),
+      NType::Eq | NType::Ne | NType::Lt | NType::Le | NType::Gt | NType::Ge => self.exec_compare(cxt),
+      NType::And | NType::Or => self.exec_logical(cxt),
+      NType::Not      => self.exec_not(cxt),
+      NType::If       => self.exec_if(cxt),
+      NType::Call     => self.exec_call(cxt),
+      NType::Define   => self.exec_define(cxt),
+      NType::Declare  => self.exec_declare(cxt),
     }
   }
   
-  fn exec_ident(&self, cxt: &mut Context) -> Result<unit::Value, error::Error> {
-    let name = self.text()?;
-    match cxt.get(&name) {
-      Some(v) => Ok(v),
-      None => Err(error::Error::UnboundVariable(name.to_owned())),
-    }
+  fn exec_ident(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    cxt.lookup(self.text()?)
   }
   
-  fn exec_number(&self, _cxt: &mut Context) -> Result<unit::Value, error::Error> {
+  fn exec_number(&self, _cxt: &mut Context) -> Result<value::Value, error::Error> {
     self.value()
   }
   
-  fn exec_assign(&self, cxt: &mut Context) -> Result<unit::Value, error::Error> {
+  fn exec_string(&self, _cxt: &mut Context) -> Result<value::Value, error::Error> {
+    Ok(value::Value::String(self.text()?.to_owned()))
+  }
+  
+  fn exec_assign(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
     let left = self.left()?;
     let right = self.right()?;
     let ident = match left.ntype {
@@ -225,13 +772,13 @@ impl Node {
     };
     let right = match right.exec(cxt) {
       Ok(right) => right,
-      Err(err) => return Err(error::Error::InvalidASTNode(format!("{}: Could not exec right: {}", self.ntype, err))),
+      Err(err) => return Err(child_error(self.ntype, "right", err)),
     };
-    cxt.set(ident, right);
+    cxt.set(ident, right.clone());
     Ok(right)
   }
   
-  fn exec_typecast(&self, cxt: &mut Context) -> Result<unit::Value, error::Error> {
+  fn exec_typecast(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
     let left = self.left()?;
     let right = self.right()?;
     let tname = match right.ntype {
@@ -240,40 +787,240 @@ impl Node {
     };
     let left = match left.exec(cxt) {
       Ok(left) => left,
-      Err(err) => return Err(error::Error::InvalidASTNode(format!("{}: Could not exec left: {}", self.ntype, err))),
+      Err(err) => return Err(child_error(self.ntype, "left", err)),
+    };
+    if value::Format::from(tname).is_some() {
+      return Ok(left); // only changes how the result is displayed
+    }
+    let left = left.number()?;
+    let to = match unit::Compound::from_with(tname, cxt.units()) {
+      Some(to) => to,
+      None => return Err(error::Error::UnknownUnit(tname.to_string())),
+    };
+    cxt.exchange(left.unit(), Some(&to));
+    match left.convert_compound(Some(&to)) {
+      Some(conv) => Ok(conv.into()),
+      None => Err(error::Error::UnitMismatch(left.unit().map(|u| { u.to_string() }).unwrap_or_default(), to.to_string())),
+    }
+  }
+  
+  fn exec_ingredient(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let left = self.left()?;
+    let right = self.right()?;
+    let name = match right.ntype {
+      NType::Ident => right.text()?,
+      _ => return Err(error::Error::InvalidASTNode(format!("{}: Expected identifier as right child, got: {}", self.ntype, right.ntype))),
+    };
+    let ingredient = match ingredient::Ingredient::from(name) {
+      Some(ingredient) => ingredient,
+      None => return Err(error::Error::InvalidASTNode(format!("{}: Unknown ingredient: {}", self.ntype, name))),
+    };
+    let left = match left.exec(cxt) {
+      Ok(left) => left,
+      Err(err) => return Err(child_error(self.ntype, "left", err)),
     };
-    Ok(match left.convert(unit::Unit::from(tname)) {
-      Some(conv) => conv,
-      None => left,
-    })
+    Ok(left.number()?.with_ingredient(Some(ingredient)).into())
   }
   
-  fn exec_arith(&self, cxt: &mut Context) -> Result<unit::Value, error::Error> {
+  fn exec_arith(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
     let left = match self.left()?.exec(cxt) {
       Ok(left) => left,
-      Err(err) => return Err(error::Error::InvalidASTNode(format!("{}: Could not exec left: {}", self.ntype, err))),
+      Err(err) => return Err(child_error(self.ntype, "left", err)),
     };
     let right = match self.right()?.exec(cxt) {
       Ok(right) => right,
-      Err(err) => return Err(error::Error::InvalidASTNode(format!("{}: Could not exec right: {}", self.ntype, err))),
+      Err(err) => return Err(child_error(self.ntype, "right", err)),
     };
+    if let (value::Value::Number(l), value::Value::Number(r)) = (&left, &right) {
+      cxt.exchange(l.unit(), r.unit()); // as in '$100 + €50' or '$1,000 / €100'
+    }
     match self.ntype {
-      NType::Add => Ok(left + right),
-      NType::Sub => Ok(left - right),
-      NType::Mul => Ok(left * right),
-      NType::Div => Ok(left / right),
-      NType::Mod => Ok(left % right),
+      NType::Add => left + right,
+      NType::Sub => left - right,
+      NType::Mul => left * right,
+      NType::Div => left / right,
+      NType::Mod => left % right,
+      NType::Pow => left.pow(right),
+      NType::Of  => left.of(right),
+      NType::PercentOf => left.percent_of(right),
+      NType::WholeOf => left.whole(right),
       _ => Err(error::Error::InvalidASTNode(format!("{}: Unsupported operation", self.ntype))),
     }
   }
   
+  fn exec_unary(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let right = match self.right()?.exec(cxt) {
+      Ok(right) => right,
+      Err(err) => return Err(child_error(self.ntype, "operand", err)),
+    };
+    match self.ntype {
+      NType::Neg => -right,
+      NType::Pos => Ok(right),
+      _ => Err(error::Error::InvalidASTNode(format!("{}: Unsupported operation", self.ntype))),
+    }
+  }
+  
+  fn exec_percent(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let right = match self.right()?.exec(cxt) {
+      Ok(right) => right.number()?,
+      Err(err) => return Err(child_error(self.ntype, "operand", err)),
+    };
+    match right.unit() {
+      None => Ok(value::Value::Percent(right.value())),
+      Some(_) => Err(error::Error::InvalidOperation(format!("Cannot express {} as a percentage", right))),
+    }
+  }
+  
+  fn exec_compare(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let left = match self.left()?.exec(cxt) {
+      Ok(left) => left,
+      Err(err) => return Err(child_error(self.ntype, "left", err)),
+    };
+    let right = match self.right()?.exec(cxt) {
+      Ok(right) => right,
+      Err(err) => return Err(child_error(self.ntype, "right", err)),
+    };
+    if let (value::Value::Number(l), value::Value::Number(r)) = (&left, &right) {
+      cxt.exchange(l.unit(), r.unit()); // as in '$100 > €90'
+    }
+    let res = match self.ntype {
+      NType::Eq => left.equals(right)?,
+      NType::Ne => !left.equals(right)?,
+      NType::Lt => left.compare(right)?.is_lt(),
+      NType::Le => left.compare(right)?.is_le(),
+      NType::Gt => left.compare(right)?.is_gt(),
+      NType::Ge => left.compare(right)?.is_ge(),
+      _ => return Err(error::Error::InvalidASTNode(format!("{}: Unsupported operation", self.ntype))),
+    };
+    Ok(value::Value::Boolean(res))
+  }
+  
+  fn exec_logical(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let left = match self.left()?.exec(cxt) {
+      Ok(left) => left.boolean()?,
+      Err(err) => return Err(child_error(self.ntype, "left", err)),
+    };
+    // the right operand is only evaluated when the left one does not
+    // already decide the result
+    match (self.ntype, left) {
+      (NType::And, false) => return Ok(value::Value::Boolean(false)),
+      (NType::Or, true) => return Ok(value::Value::Boolean(true)),
+      _ => {},
+    };
+    match self.right()?.exec(cxt) {
+      Ok(right) => Ok(value::Value::Boolean(right.boolean()?)),
+      Err(err) => Err(child_error(self.ntype, "right", err)),
+    }
+  }
+  
+  fn exec_not(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    match self.right()?.exec(cxt) {
+      Ok(right) => Ok(value::Value::Boolean(!right.boolean()?)),
+      Err(err) => Err(child_error(self.ntype, "operand", err)),
+    }
+  }
+  
+  fn exec_if(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let args = self.args()?;
+    if args.len() != 3 {
+      return Err(error::Error::InvalidASTNode(format!("{}: Expected condition and two branches", self.ntype)));
+    }
+    let cond = match args[0].exec(cxt) {
+      Ok(cond) => cond.boolean()?,
+      Err(err) => return Err(child_error(self.ntype, "condition", err)),
+    };
+    if cond {
+      args[1].exec(cxt)
+    }else{
+      args[2].exec(cxt)
+    }
+  }
+  
+  fn exec_call(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let name = self.text()?;
+    let f = match cxt.func(name) {
+      Some(f) => f,
+      None => return Err(error::Error::UnboundFunction(name.to_owned())),
+    };
+    let mut args: Vec<value::Value> = Vec::new();
+    for arg in self.args()? {
+      match arg.exec(cxt) {
+        Ok(arg) => args.push(arg),
+        Err(err) => return Err(child_error(name, "argument", err)),
+      };
+    }
+    if let func::Function::Builtin(_) = f {
+      // built-in functions convert every argument to the first unit given
+      let units: Vec<unit::Compound> = args.iter().filter_map(|arg| {
+        match arg {
+          value::Value::Number(n) => n.unit().cloned(),
+          _ => None,
+        }
+      }).collect();
+      for unit in &units {
+        cxt.exchange(Some(unit), units.first()); // as in 'max($100, €90)'
+      }
+    }
+    f.apply(cxt, &args)
+  }
+  
+  fn exec_define(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let name = self.text()?;
+    let mut params: Vec<String> = Vec::new();
+    for param in self.args()? {
+      match param.name() {
+        Some(p) => params.push(p.to_owned()),
+        None => return Err(error::Error::InvalidASTNode(format!("{}: Expected identifier as parameter, got: {}", self.ntype, param.ntype))),
+      };
+    }
+    cxt.define(name, func::Function::Defined(func::Defined::new(name, params, self.right()?.as_ref().clone())));
+    Err(error::Error::NoValue) // a definition produces no result
+  }
+  
+  /// Declare a unit. When it is declared as a quantity of a word that is
+  /// not yet a unit, as in 'unit crate = 24 bottles', the word is declared
+  /// first, as a base unit of its own.
+  fn exec_declare(&self, cxt: &mut Context) -> Result<value::Value, error::Error> {
+    let name = self.text()?;
+    if unit::Compound::from(name).is_some() {
+      return Err(error::Error::InvalidOperation(format!("Cannot declare {}, which is already a unit", name)));
+    }
+    let def = self.right()?;
+    if def.ntype == NType::Typecast {
+      if let Some(base) = def.right()?.name() {
+        if unit::Compound::from_with(base, cxt.units()).is_none() && value::Format::from(base).is_none() {
+          cxt.declare(base, None);
+        }
+      }
+    }
+    let def = match def.exec(cxt) {
+      Ok(def) => def.number()?,
+      Err(err) => return Err(child_error(self.ntype, "definition", err)),
+    };
+    cxt.declare(name, Some(&def));
+    Err(error::Error::NoValue) // a declaration produces no result
+  }
+  
   pub fn print(&self) -> Result<String, error::Error> {
     match self.ntype {
       NType::Ident    => self.print_ident(),
       NType::Number   => self.print_number(),
+      NType::String   => self.print_string(),
       NType::Assign   => self.print_assign(),
       NType::Typecast => self.print_typecast(),
-      NType::Add | NType::Sub | NType::Mul | NType::Div | NType::Mod => self.print_arith(),
+      NType::Ingredient => self.print_ingredient(),
+      NType::Add | NType::Sub | NType::Mul | NType::Div | NType::Mod | NType::Pow => self.print_arith(),
+      NType::Of | NType::PercentOf => self.print_arith(),
+      NType::WholeOf  => self.print_whole_of(),
+      NType::Neg | NType::Pos => self.print_unary(),
+      NType::Percent  => self.print_percent(),
+      NType::Eq | NType::Ne | NType::Lt | NType::Le | NType::Gt | NType::Ge => self.print_arith(),
+      NType::And | NType::Or => self.print_arith(),
+      NType::Not      => self.print_not(),
+      NType::If       => self.print_if(),
+      NType::Call     => self.print_call(),
+      NType::Define   => self.print_define(),
+      NType::Declare  => self.print_declare(),
     }
   }
   
@@ -285,10 +1032,58 @@ impl Node {
     Ok(format!("{}", self.value()?))
   }
   
+  fn print_string(&self) -> Result<String, error::Error> {
+    Ok(format!("\"{}\"", self.text()?.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+  }
+  
   fn print_arith(&self) -> Result<String, error::Error> {
     Ok(format!("({} {} {})", self.left()?.print()?, self.ntype, self.right()?.print()?))
   }
   
+  fn print_unary(&self) -> Result<String, error::Error> {
+    Ok(format!("({}{})", self.ntype, self.right()?.print()?))
+  }
+  
+  fn print_percent(&self) -> Result<String, error::Error> {
+    Ok(format!("{}%", self.right()?.print()?))
+  }
+  
+  fn print_whole_of(&self) -> Result<String, error::Error> {
+    Ok(format!("({} is {} of what)", self.left()?.print()?, self.right()?.print()?))
+  }
+  
+  fn print_not(&self) -> Result<String, error::Error> {
+    Ok(format!("({} {})", self.ntype, self.right()?.print()?))
+  }
+  
+  fn print_if(&self) -> Result<String, error::Error> {
+    let args = self.args()?;
+    if args.len() != 3 {
+      return Err(error::Error::InvalidASTNode(format!("{}: Expected condition and two branches", self.ntype)));
+    }
+    Ok(format!("(if {} then {} else {})", args[0].print()?, args[1].print()?, args[2].print()?))
+  }
+  
+  fn print_call(&self) -> Result<String, error::Error> {
+    let mut args: Vec<String> = Vec::new();
+    for arg in self.args()? {
+      args.push(arg.print()?);
+    }
+    Ok(format!("{}({})", self.text()?, args.join(", ")))
+  }
+  
+  fn print_define(&self) -> Result<String, error::Error> {
+    let mut params: Vec<String> = Vec::new();
+    for param in self.args()? {
+      params.push(param.print()?);
+    }
+    Ok(format!("{}({}) = {}", self.text()?, params.join(", "), self.right()?.print()?))
+  }
+  
+  fn print_declare(&self) -> Result<String, error::Error> {
+    Ok(format!("unit {} = {}", self.text()?, self.right()?.print()?))
+  }
+  
   fn print_assign(&self) -> Result<String, error::Error> {
     Ok(format!("({} {} {})", self.left()?.print()?, self.ntype, self.right()?.print()?))
   }
@@ -296,45 +1091,153 @@ impl Node {
   fn print_typecast(&self) -> Result<String, error::Error> {
     Ok(format!("{}({})", self.right()?.print()?, self.left()?.print()?))
   }
+  
+  fn print_ingredient(&self) -> Result<String, error::Error> {
+    Ok(format!("({} {})", self.left()?.print()?, self.right()?.print()?))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
   
+  #[test]
+  fn exec_lines() {
+    assert!(Context::new().lookup("prev").is_err());
+    assert_eq!(Ok(value::Value::raw(0.0)), Context::new().lookup("sum"));
+    
+    let mut cxt = Context::new();
+    cxt.end_line("10 g", Some(value::Value::new(10.0, unit::Unit::Gram)));
+    cxt.end_line("Nothing here", None);
+    cxt.end_line("1 kg", Some(value::Value::new(1.0, unit::Unit::Kilogram)));
+    cxt.end_line("500 g", Some(value::Value::new(500.0, unit::Unit::Gram)));
+    assert_eq!(Ok(value::Value::new(500.0, unit::Unit::Gram)), cxt.lookup("prev"));
+    assert_eq!(Ok(value::Value::new(500.0, unit::Unit::Gram)), cxt.lookup("ans"));
+    assert_eq!(Ok(value::Value::new(10.0, unit::Unit::Gram)), cxt.lookup("line1"));
+    assert!(cxt.lookup("line2").is_err()); // no result
+    assert!(cxt.lookup("line9").is_err());
+    assert!(cxt.lookup("line0").is_err());
+    assert_eq!(Ok(value::Value::new(1510.0, unit::Unit::Gram)), cxt.lookup("sum"));
+    
+    cxt.end_line("", None);
+    cxt.end_line("2", Some(value::Value::raw(2.0)));
+    cxt.end_line("yes", Some(value::Value::Boolean(true)));
+    cxt.end_line("4", Some(value::Value::raw(4.0)));
+    assert_eq!(Ok(value::Value::raw(6.0)), cxt.lookup("total"));
+    assert_eq!(Ok(value::Value::raw(3.0)), cxt.lookup("average"));
+    
+    cxt.end_block();
+    assert!(cxt.lookup("average").is_err());
+    
+    cxt.set("sum", value::Value::raw(99.0));
+    assert_eq!(Ok(value::Value::raw(99.0)), cxt.lookup("sum")); // variables take precedence
+    
+    let mut cxt = Context::new();
+    cxt.end_line("10", Some(value::Value::raw(10.0)));
+    cxt.end_line("20", Some(value::Value::raw(20.0)));
+    assert_eq!(Ok(value::Value::raw(30.0)), cxt.lookup("sum"));
+    cxt.end_line("sum", Some(value::Value::raw(30.0)));
+    cxt.end_line("5", Some(value::Value::raw(5.0)));
+    assert_eq!(Ok(value::Value::raw(35.0)), cxt.lookup("total")); // the sum is not counted again
+  }
+  
   #[test]
   fn exec_simple() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = Node::new_ident("a");
-    assert_eq!(Ok(unit::Value::raw(1.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), n.exec(&mut cxt));
     
     let n = Node::new_number(1.25);
-    assert_eq!(Ok(unit::Value::raw(1.25)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.25)), n.exec(&mut cxt));
+    
+    let n = Node::new_add(Node::new_string("a is "), Node::new_ident("a"));
+    assert_eq!(Ok(value::Value::String("a is 1".to_string())), n.exec(&mut cxt));
+    assert_eq!(r#"("a is " + a)"#, n.to_string());
+    
+    let n = Node::new_string("Say \"hi\"\n");
+    assert_eq!(r#""Say \"hi\"\n""#, n.to_string());
     
     let n = Node::new_add(Node::new_ident("a"), Node::new_ident("b"));
-    assert_eq!(Ok(unit::Value::raw(3.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), n.exec(&mut cxt));
     
     let n = Node::new_sub(Node::new_ident("a"), Node::new_ident("c"));
-    assert_eq!(Ok(unit::Value::raw(-2.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(-2.0)), n.exec(&mut cxt));
     
     let n = Node::new_mul(Node::new_ident("a"), Node::new_ident("c"));
-    assert_eq!(Ok(unit::Value::raw(3.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), n.exec(&mut cxt));
     
     let n = Node::new_div(Node::new_ident("a"), Node::new_ident("b"));
-    assert_eq!(Ok(unit::Value::raw(0.5)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(0.5)), n.exec(&mut cxt));
     
     let n = Node::new_mod(Node::new_ident("c"), Node::new_ident("b"));
-    assert_eq!(Ok(unit::Value::raw(1.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_pow(Node::new_ident("b"), Node::new_ident("c"));
+    assert_eq!(Ok(value::Value::raw(8.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_neg(Node::new_ident("b"));
+    assert_eq!(Ok(value::Value::raw(-2.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_percent(Node::new_ident("b"));
+    assert_eq!(Ok(value::Value::Percent(2.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_of(Node::new_percent(Node::new_number(50.0)), Node::new_ident("c"));
+    assert_eq!(Ok(value::Value::raw(1.5)), n.exec(&mut cxt));
+    
+    let n = Node::new_percent_of(Node::new_ident("a"), Node::new_number(4.0));
+    assert_eq!(Ok(value::Value::Percent(25.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_whole_of(Node::new_ident("c"), Node::new_percent(Node::new_number(30.0)));
+    assert_eq!(Ok(value::Value::raw(10.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_pos(Node::new_ident("b"));
+    assert_eq!(Ok(value::Value::raw(2.0)), n.exec(&mut cxt));
+    
+    cxt.define("max", func::Function::Builtin(func::Builtin::new("max", func::Arity::AtLeast(1), func::Units::Preserve, |a| { a.iter().cloned().fold(f64::NEG_INFINITY, f64::max) })));
+    let n = Node::new_call("max", vec![Node::new_ident("a"), Node::new_ident("c"), Node::new_ident("b")]);
+    assert_eq!(Ok(value::Value::raw(3.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_call("min", vec![Node::new_ident("a")]);
+    assert_eq!(Err(error::Error::UnboundFunction("min".to_string())), n.exec(&mut cxt));
+    
+    let n = Node::new_define("avg", vec![Node::new_ident("x"), Node::new_ident("y")], Node::new_div(Node::new_add(Node::new_ident("x"), Node::new_ident("y")), Node::new_number(2.0)));
+    assert_eq!(Err(error::Error::NoValue), n.exec(&mut cxt));
+    let n = Node::new_call("avg", vec![Node::new_ident("a"), Node::new_ident("c")]);
+    assert_eq!(Ok(value::Value::raw(2.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_lt(Node::new_ident("a"), Node::new_ident("b"));
+    assert_eq!(Ok(value::Value::Boolean(true)), n.exec(&mut cxt));
+    
+    let n = Node::new_ge(Node::new_ident("a"), Node::new_ident("b"));
+    assert_eq!(Ok(value::Value::Boolean(false)), n.exec(&mut cxt));
+    
+    let n = Node::new_ne(Node::new_ident("a"), Node::new_ident("b"));
+    assert_eq!(Ok(value::Value::Boolean(true)), n.exec(&mut cxt));
+    
+    let n = Node::new_and(Node::new_lt(Node::new_ident("a"), Node::new_ident("b")), Node::new_not(Node::new_eq(Node::new_ident("b"), Node::new_ident("c"))));
+    assert_eq!(Ok(value::Value::Boolean(true)), n.exec(&mut cxt));
+    
+    let n = Node::new_or(Node::new_gt(Node::new_ident("a"), Node::new_ident("b")), Node::new_ident("nope")); // short-circuits only on true
+    assert!(n.exec(&mut cxt).is_err());
+    
+    let n = Node::new_and(Node::new_gt(Node::new_ident("a"), Node::new_ident("b")), Node::new_ident("nope")); // never evaluates right
+    assert_eq!(Ok(value::Value::Boolean(false)), n.exec(&mut cxt));
+    
+    let n = Node::new_if(Node::new_le(Node::new_ident("c"), Node::new_ident("b")), Node::new_ident("a"), Node::new_ident("c"));
+    assert_eq!(Ok(value::Value::raw(3.0)), n.exec(&mut cxt));
+    
+    let n = Node::new_if(Node::new_ident("a"), Node::new_ident("a"), Node::new_ident("c")); // condition must be boolean
+    assert!(n.exec(&mut cxt).is_err());
     
     let n = Node::new_assign(Node::new_ident("d"), Node::new_number(123.0));
-    assert_eq!(Ok(unit::Value::raw(123.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(123.0)), n.exec(&mut cxt));
     
     let n = Node::new_typecast(Node::new_ident("d"), Node::new_ident("kg"));
-    assert_eq!(Ok(unit::Value::new(123.0, unit::Unit::Kilogram)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::new(123.0, unit::Unit::Kilogram)), n.exec(&mut cxt));
   }
-  
+
 }
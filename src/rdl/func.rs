@@ -0,0 +1,271 @@
+use std::fmt;
+
+use crate::rdl::unit;
+use crate::rdl::value;
+use crate::rdl::exec;
+use crate::rdl::error;
+
+const MAX_DEPTH: usize = 64; // maximum nesting of calls to defined functions
+
+/// A function available to a document: either one of the built-in
+/// functions or a formula defined by the document itself.
+#[derive(Debug, Clone)]
+pub enum Function {
+  Builtin(Builtin),
+  Defined(Defined),
+}
+
+impl Function {
+  pub fn apply(&self, cxt: &mut exec::Context, args: &[value::Value]) -> Result<value::Value, error::Error> {
+    match self {
+      Function::Builtin(f) => {
+        let mut nums: Vec<unit::Value> = Vec::new();
+        for arg in args {
+          nums.push(arg.number()?);
+        }
+        Ok(f.apply(&nums)?.into())
+      },
+      Function::Defined(f) => f.apply(cxt, args),
+    }
+  }
+}
+
+/// Describes how a function treats the units of its arguments.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Units {
+  Preserve, // arguments are converted to the first unit given; the result has that unit
+  Unitless, // arguments must not have units and neither does the result
+  Root,     // the result has the square root of the argument's unit
+}
+
+/// The number of arguments a function accepts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Arity {
+  Exactly(usize),
+  AtLeast(usize),
+}
+
+impl Arity {
+  pub fn accepts(&self, n: usize) -> bool {
+    match self {
+      Arity::Exactly(m) => n == *m,
+      Arity::AtLeast(m) => n >= *m,
+    }
+  }
+}
+
+impl fmt::Display for Arity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Arity::Exactly(n) => write!(f, "{}", n),
+      Arity::AtLeast(n) => write!(f, "at least {}", n),
+    }
+  }
+}
+
+/// A function implemented natively. The evaluator operates on the numeric
+/// values of the arguments only; units are handled according to the
+/// function's declared policy.
+#[derive(Copy, Clone)]
+pub struct Builtin {
+  pub name: &'static str,
+  pub arity: Arity,
+  pub units: Units,
+  eval: fn(&[f64]) -> f64,
+}
+
+impl fmt::Debug for Builtin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "<builtin {}>", self.name)
+  }
+}
+
+impl Builtin {
+  pub fn new(name: &'static str, arity: Arity, units: Units, eval: fn(&[f64]) -> f64) -> Builtin {
+    Builtin{
+      name: name,
+      arity: arity,
+      units: units,
+      eval: eval,
+    }
+  }
+  
+  pub fn apply(&self, args: &[unit::Value]) -> Result<unit::Value, error::Error> {
+    if !self.arity.accepts(args.len()) {
+      return Err(error::Error::InvalidArguments(format!("{}: Expected {} arguments, got {}", self.name, self.arity, args.len())));
+    }
+    match self.units {
+      Units::Preserve => self.apply_preserve(args),
+      Units::Unitless => self.apply_unitless(args),
+      Units::Root     => self.apply_root(args),
+    }
+  }
+  
+  fn apply_preserve(&self, args: &[unit::Value]) -> Result<unit::Value, error::Error> {
+    let target = args.iter().find_map(|arg| { arg.unit() });
+    let mut raw: Vec<f64> = Vec::new();
+    for arg in args {
+      match arg.convert_compound(target) {
+        Some(conv) => raw.push(conv.value()),
+        None => return Err(error::Error::InvalidArguments(format!("{}: Cannot convert {} to a common unit", self.name, arg))),
+      };
+    }
+    Ok(unit::Value::option((self.eval)(&raw), target.cloned()))
+  }
+  
+  fn apply_unitless(&self, args: &[unit::Value]) -> Result<unit::Value, error::Error> {
+    let mut raw: Vec<f64> = Vec::new();
+    for arg in args {
+      match arg.unit() {
+        Some(_) => return Err(error::Error::InvalidArguments(format!("{}: Expected a value without units, got {}", self.name, arg))),
+        None => raw.push(arg.value()),
+      };
+    }
+    Ok(unit::Value::raw((self.eval)(&raw)))
+  }
+  
+  fn apply_root(&self, args: &[unit::Value]) -> Result<unit::Value, error::Error> {
+    let raw: Vec<f64> = args.iter().map(|arg| { arg.value() }).collect();
+    let unit = match args[0].unit() {
+      None => None,
+      Some(u) => match u.pow(0.5) {
+        Some(root) => Some(root),
+        None => return Err(error::Error::InvalidArguments(format!("{}: Cannot take the root of {}", self.name, args[0]))),
+      },
+    };
+    Ok(unit::Value::option((self.eval)(&raw), unit))
+  }
+}
+
+/// A function defined inline by a document, as in:
+/// 
+///   margin(price, cost) = (price - cost) / price
+/// 
+/// Arguments are bound to the parameter names for the duration of the call,
+/// shadowing any variables of the same name, which are restored afterwards.
+#[derive(Debug, Clone)]
+pub struct Defined {
+  pub name: String,
+  pub params: Vec<String>,
+  body: exec::Node,
+}
+
+impl Defined {
+  pub fn new(name: &str, params: Vec<String>, body: exec::Node) -> Defined {
+    Defined{
+      name: name.to_string(),
+      params: params,
+      body: body,
+    }
+  }
+  
+  pub fn apply(&self, cxt: &mut exec::Context, args: &[value::Value]) -> Result<value::Value, error::Error> {
+    if args.len() != self.params.len() {
+      return Err(error::Error::InvalidArguments(format!("{}: Expected {} arguments, got {}", self.name, self.params.len(), args.len())));
+    }
+    if cxt.depth() >= MAX_DEPTH {
+      return Err(error::Error::InvalidOperation(format!("{}: Too many nested calls", self.name)));
+    }
+    
+    let saved: Vec<Option<value::Value>> = self.params.iter().map(|p| { cxt.get(p) }).collect();
+    for (p, a) in self.params.iter().zip(args) {
+      cxt.set(p, a.clone());
+    }
+    
+    cxt.descend();
+    let res = self.body.exec(cxt);
+    cxt.ascend();
+    
+    for (p, v) in self.params.iter().zip(saved) {
+      match v {
+        Some(v) => cxt.set(p, v),
+        None    => cxt.unset(p),
+      };
+    }
+    
+    res
+  }
+}
+
+/// The standard math library available to every document.
+pub fn stdlib() -> Vec<Builtin> {
+  vec![
+    Builtin::new("sqrt",  Arity::Exactly(1), Units::Root,     |a| { a[0].sqrt() }),
+    Builtin::new("abs",   Arity::Exactly(1), Units::Preserve, |a| { a[0].abs() }),
+    Builtin::new("floor", Arity::Exactly(1), Units::Preserve, |a| { a[0].floor() }),
+    Builtin::new("ceil",  Arity::Exactly(1), Units::Preserve, |a| { a[0].ceil() }),
+    Builtin::new("round", Arity::Exactly(1), Units::Preserve, |a| { a[0].round() }),
+    Builtin::new("min",   Arity::AtLeast(1), Units::Preserve, |a| { a.iter().cloned().fold(f64::INFINITY, f64::min) }),
+    Builtin::new("max",   Arity::AtLeast(1), Units::Preserve, |a| { a.iter().cloned().fold(f64::NEG_INFINITY, f64::max) }),
+    Builtin::new("ln",    Arity::Exactly(1), Units::Unitless, |a| { a[0].ln() }),
+    Builtin::new("log10", Arity::Exactly(1), Units::Unitless, |a| { a[0].log10() }),
+    Builtin::new("exp",   Arity::Exactly(1), Units::Unitless, |a| { a[0].exp() }),
+    Builtin::new("sin",   Arity::Exactly(1), Units::Unitless, |a| { a[0].sin() }),
+    Builtin::new("cos",   Arity::Exactly(1), Units::Unitless, |a| { a[0].cos() }),
+    Builtin::new("tan",   Arity::Exactly(1), Units::Unitless, |a| { a[0].tan() }),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  
+  fn call(name: &str, args: &[unit::Value]) -> Result<unit::Value, error::Error> {
+    match stdlib().iter().find(|f| { f.name == name }) {
+      Some(f) => f.apply(args),
+      None => panic!("No such function: {}", name),
+    }
+  }
+  
+  #[test]
+  fn apply_builtin() {
+    assert_eq!(Ok(unit::Value::raw(3.0)), call("sqrt", &[unit::Value::raw(9.0)]));
+    assert_eq!(Ok(unit::Value::raw(2.0)), call("abs", &[unit::Value::raw(-2.0)]));
+    assert_eq!(Ok(unit::Value::raw(1.0)), call("floor", &[unit::Value::raw(1.5)]));
+    assert_eq!(Ok(unit::Value::raw(2.0)), call("ceil", &[unit::Value::raw(1.5)]));
+    assert_eq!(Ok(unit::Value::raw(3.0)), call("round", &[unit::Value::raw(2.5)]));
+    assert_eq!(Ok(unit::Value::raw(1.0)), call("min", &[unit::Value::raw(3.0), unit::Value::raw(1.0), unit::Value::raw(2.0)]));
+    assert_eq!(Ok(unit::Value::raw(3.0)), call("max", &[unit::Value::raw(3.0), unit::Value::raw(1.0), unit::Value::raw(2.0)]));
+    assert_eq!(Ok(unit::Value::raw(0.0)), call("ln", &[unit::Value::raw(1.0)]));
+    assert_eq!(Ok(unit::Value::raw(3.0)), call("log10", &[unit::Value::raw(1000.0)]));
+    assert_eq!(Ok(unit::Value::raw(1.0)), call("exp", &[unit::Value::raw(0.0)]));
+    assert_eq!(Ok(unit::Value::raw(0.0)), call("sin", &[unit::Value::raw(0.0)]));
+    assert_eq!(Ok(unit::Value::raw(1.0)), call("cos", &[unit::Value::raw(0.0)]));
+    assert_eq!(Ok(unit::Value::raw(0.0)), call("tan", &[unit::Value::raw(0.0)]));
+  }
+  
+  #[test]
+  fn apply_units() {
+    assert_eq!(Ok(unit::Value::new(2.0, unit::Unit::Cup)), call("abs", &[unit::Value::new(-2.0, unit::Unit::Cup)]));
+    assert_eq!(Ok(unit::Value::new(3.0, unit::Unit::Tablespoon)), call("min", &[unit::Value::new(3.0, unit::Unit::Tablespoon), unit::Value::new(1.0, unit::Unit::Cup)]));
+    assert_eq!(Ok(unit::Value::new(1000.0, unit::Unit::Gram)), call("max", &[unit::Value::new(10.0, unit::Unit::Gram), unit::Value::new(1.0, unit::Unit::Kilogram)]));
+    assert_eq!(Ok(unit::Value::new(5.0, unit::Unit::Gram)), call("max", &[unit::Value::raw(5.0), unit::Value::new(1.0, unit::Unit::Gram)]));
+    
+    assert!(call("max", &[unit::Value::new(1.0, unit::Unit::Gram), unit::Value::new(1.0, unit::Unit::Cup)]).is_err());
+    assert!(call("sin", &[unit::Value::new(1.0, unit::Unit::Gram)]).is_err());
+    assert!(call("sqrt", &[unit::Value::new(4.0, unit::Unit::Gram)]).is_err());
+  }
+  
+  #[test]
+  fn apply_defined() {
+    let mut cxt = exec::Context::new();
+    cxt.set("x", value::Value::raw(10.0));
+    
+    let f = Defined::new("double", vec!["x".to_string()], exec::Node::new_mul(exec::Node::new_ident("x"), exec::Node::new_number(2.0)));
+    assert_eq!(Ok(value::Value::raw(6.0)), f.apply(&mut cxt, &[value::Value::raw(3.0)]));
+    assert_eq!(Some(value::Value::raw(10.0)), cxt.get("x")); // restored after the call
+    
+    let f = Defined::new("twice", vec!["y".to_string()], exec::Node::new_add(exec::Node::new_ident("x"), exec::Node::new_ident("y")));
+    assert_eq!(Ok(value::Value::raw(13.0)), f.apply(&mut cxt, &[value::Value::raw(3.0)]));
+    assert_eq!(None, cxt.get("y")); // unbound after the call
+    
+    assert!(f.apply(&mut cxt, &[]).is_err());
+  }
+  
+  #[test]
+  fn apply_arity() {
+    assert!(call("sqrt", &[]).is_err());
+    assert!(call("sqrt", &[unit::Value::raw(1.0), unit::Value::raw(2.0)]).is_err());
+    assert!(call("max", &[]).is_err());
+  }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+/// Grams in one US cup of water, which is also its volume in milliliters.
+const CUP: f64 = 236.5882365;
+
+/// A cooking ingredient with a known density, which lets an amount of it
+/// be converted between volume and mass, as in '1 cup flour in g'.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Ingredient {
+  Water,
+  Milk,
+  Cream,
+  Butter,
+  Oil,
+  Flour,
+  Sugar,
+  Honey,
+  Rice,
+  Oats,
+  Salt,
+  Cocoa,
+}
+
+impl Ingredient {
+  pub fn from(name: &str) -> Option<Ingredient> {
+    match name.trim().to_lowercase().as_str() {
+      "water"  => Some(Ingredient::Water),
+      "milk"   => Some(Ingredient::Milk),
+      "cream"  => Some(Ingredient::Cream),
+      "butter" => Some(Ingredient::Butter),
+      "oil"    => Some(Ingredient::Oil),
+      "flour"  => Some(Ingredient::Flour),
+      "sugar"  => Some(Ingredient::Sugar),
+      "honey"  => Some(Ingredient::Honey),
+      "rice"   => Some(Ingredient::Rice),
+      "oats"   => Some(Ingredient::Oats),
+      "salt"   => Some(Ingredient::Salt),
+      "cocoa"  => Some(Ingredient::Cocoa),
+      _        => None,
+    }
+  }
+  
+  /// The weight of one US cup of this ingredient, in grams, as it is
+  /// usually measured: flour is spooned in, sugar is granulated.
+  fn grams_per_cup(&self) -> f64 {
+    match self {
+      Ingredient::Water  => CUP,
+      Ingredient::Milk   => 242.0,
+      Ingredient::Cream  => 238.0,
+      Ingredient::Butter => 227.0,
+      Ingredient::Oil    => 218.0,
+      Ingredient::Flour  => 120.0,
+      Ingredient::Sugar  => 198.0,
+      Ingredient::Honey  => 336.0,
+      Ingredient::Rice   => 190.0,
+      Ingredient::Oats   => 89.0,
+      Ingredient::Salt   => 288.0,
+      Ingredient::Cocoa  => 84.0,
+    }
+  }
+  
+  /// The density of this ingredient in kilograms per cubic meter.
+  pub fn density(&self) -> f64 {
+    self.grams_per_cup() / CUP * 1000.0
+  }
+}
+
+impl fmt::Display for Ingredient {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Water  => write!(f, "water"),
+      Self::Milk   => write!(f, "milk"),
+      Self::Cream  => write!(f, "cream"),
+      Self::Butter => write!(f, "butter"),
+      Self::Oil    => write!(f, "oil"),
+      Self::Flour  => write!(f, "flour"),
+      Self::Sugar  => write!(f, "sugar"),
+      Self::Honey  => write!(f, "honey"),
+      Self::Rice   => write!(f, "rice"),
+      Self::Oats   => write!(f, "oats"),
+      Self::Salt   => write!(f, "salt"),
+      Self::Cocoa  => write!(f, "cocoa"),
+    }
+  }
+}
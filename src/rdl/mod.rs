@@ -2,7 +2,11 @@ pub mod error;
 pub mod scan;
 pub mod parse;
 pub mod exec;
+pub mod func;
 pub mod unit;
+pub mod ingredient;
+pub mod currency;
+pub mod value;
 
 use scan::Scanner;
 use parse::Parser;
@@ -13,6 +17,7 @@ use crate::text::attrs;
 pub struct Options {
   pub verbose: bool, // enable verbose output
   pub debug: bool,   // enable debugging
+  pub packed: bool,  // display results in mixed units, unless an expression asks otherwise
 }
 
 pub fn render_with_options(cxt: &mut Context, text: &str, boff0: usize, boff1: usize, attrs: Option<&Vec<attrs::Attributes>>, opts: Option<&Options>) -> (attrs::Attributed, attrs::Attributed) {
@@ -21,14 +26,45 @@ pub fn render_with_options(cxt: &mut Context, text: &str, boff0: usize, boff1: u
   let mut s1: Vec<attrs::Span> = Vec::new();
   let mut p = Parser::new(Scanner::new(text));
   let mut i = 0;
+  let mut last = None;
+  if text.trim_start().starts_with('#') {
+    cxt.end_block(); // a heading starts a new block and is not evaluated
+    cxt.end_line(text, None);
+    return (
+      attrs::Attributed::new_with_str(text, s0),
+      attrs::Attributed::new_with_string(g, s1),
+    );
+  }
   loop {
+    p.set_units(cxt.units().clone()); // including any declared earlier on this line
+    p.set_vars(cxt.vars());
     let exp = match p.parse() {
       Ok(exp) => exp,
       Err(_)  => break,
     };
     
-    let res = match exp.ast.exec(cxt) {
-      Ok(res) => res.to_string(),
+    let format = match exp.ast.format() {
+      Some(format) => format,
+      None => match opts {
+        Some(opts) if opts.packed => value::Format::Packed,
+        _ => value::Format::Raw,
+      },
+    };
+    
+    let converted = exp.ast.is_converted();
+    let res = exp.ast.exec(cxt);
+    let exchanged = cxt.exchanged();
+    let res = match res {
+      Ok(res) => {
+        let text = if exchanged {
+          format!("{} (rates of {})", res.format(format, converted), cxt.rates().date()) // say how current the conversion is
+        }else{
+          res.format(format, converted)
+        };
+        last = Some(res);
+        text
+      },
+      Err(err @ (error::Error::UnitMismatch(..) | error::Error::UnknownUnit(..))) => err.to_string(), // say why there is no result
       Err(_)  => continue,
     };
     
@@ -56,6 +92,7 @@ pub fn render_with_options(cxt: &mut Context, text: &str, boff0: usize, boff1: u
     
     i += 1;
   }
+  cxt.end_line(text, last);
   (
     attrs::Attributed::new_with_str(text, s0),
     attrs::Attributed::new_with_string(g, s1),
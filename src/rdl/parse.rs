@@ -1,12 +1,22 @@
 use std::fmt;
 use std::ops;
+use std::cmp;
 
 use crate::rdl;
 use crate::rdl::scan::{self, Scanner, TType};
 use crate::rdl::exec::{Context, Node};
 use crate::rdl::unit;
+use crate::rdl::ingredient;
+use crate::rdl::currency;
+use crate::rdl::value;
 use crate::rdl::error;
 
+/// Words with a meaning in expressions, which can't be used as labels.
+const KEYWORDS: [&str; 10] = ["is", "of", "what", "not", "and", "or", "mod", "if", "then", "else"];
+
+/// Suffixes that make a number an ordinal, as in '1st'.
+const ORDINALS: [&str; 4] = ["st", "nd", "rd", "th"];
+
 #[derive(Debug, PartialEq)]
 pub struct Expr {
   pub range: ops::Range<usize>,
@@ -21,55 +31,282 @@ impl fmt::Display for Expr {
 
 pub struct Parser<'a> {
   scan: Scanner<'a>,
+  units: unit::Registry, // units declared in the document so far
+  vars: Vec<String>, // variables set in the document so far
 }
 
 impl<'a> Parser<'a> {
   pub fn new(scan: Scanner<'a>) -> Parser<'a> {
     Parser{
       scan: scan,
+      units: unit::Registry::default(),
+      vars: Vec::new(),
     }
   }
   
+  /// Recognize the units declared in the document so far, as well as the
+  /// built-in ones.
+  pub fn set_units(&mut self, units: unit::Registry) {
+    self.units = units;
+  }
+  
+  /// Recognize the variables set in the document so far, which are never
+  /// taken for labels.
+  pub fn set_vars(&mut self, vars: Vec<String>) {
+    self.vars = vars;
+  }
+  
   pub fn parse(&mut self) -> Result<Expr, error::Error> {
     self.scan.discard_fn(|ttype| {
       ttype == TType::Whitespace ||
-      ttype == TType::Verbatim
+      ttype == TType::Verbatim   ||
+      ttype == TType::Comma
     });
     self.parse_enter()
   }
   
   fn parse_enter(&mut self) -> Result<Expr, error::Error> {
-    self.parse_assign()
+    let snapshot = self.scan.clone();
+    match self.parse_declare() {
+      Ok(exp) => Ok(exp),
+      Err(_) => {
+        self.scan = snapshot;
+        self.parse_assign()
+      },
+    }
+  }
+  
+  /// Parse a unit declaration, as in 'unit sprint = 2 weeks'. The word is
+  /// common in prose, so nothing is declared unless the whole form is
+  /// present.
+  fn parse_declare(&mut self) -> Result<Expr, error::Error> {
+    let tok = self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "unit" })?;
+    self.scan.discard(TType::Whitespace);
+    let name = self.scan.expect_token(TType::Ident)?;
+    self.scan.discard(TType::Whitespace);
+    self.scan.expect_token(TType::Assign)?;
+    self.scan.discard(TType::Whitespace);
+    let snapshot = self.scan.clone();
+    let def = match self.parse_base_quantity() {
+      Ok(def) => def,
+      Err(_) => {
+        self.scan = snapshot;
+        self.parse_arith()?
+      },
+    };
+    Ok(Expr{
+      range: tok.range.start..def.range.end,
+      ast: Node::new_declare(&name.ttext, def.ast),
+    })
+  }
+  
+  /// Parse a quantity of a word that is not a unit yet, as in '24 bottles',
+  /// which declares it as one.
+  fn parse_base_quantity(&mut self) -> Result<Expr, error::Error> {
+    let num = self.scan.expect_token(TType::Number)?;
+    self.scan.discard(TType::Whitespace);
+    let units = &self.units;
+    let base = self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && !KEYWORDS.contains(&tok.ttext.as_str()) && unit::Compound::from_with(&tok.ttext, units).is_none()
+    })?;
+    Ok(Expr{
+      range: num.range.start..base.range.end,
+      ast: Node::new_typecast(Node::new_number(num.number()?), Node::new_ident(&base.ttext)),
+    })
   }
   
   fn parse_assign(&mut self) -> Result<Expr, error::Error> {
     self.scan.discard(TType::Whitespace);
     
     let left = match self.parse_ident() {
-      Ok(left) => left,
-      Err(_)   => return self.parse_typecast(),
+      Ok(left) => self.parse_call_left(left)?,
+      Err(_)   => return self.parse_logic(),
     };
 
     self.scan.discard(TType::Whitespace);
     
     match self.scan.expect_token(TType::Assign) {
       Ok(_)  => {},
-      Err(_) => return self.parse_typecast_left(left),
+      Err(_) => return self.parse_logic_left(left),
     };
     
     self.scan.discard(TType::Whitespace);
     
-    let right = match self.parse_typecast() {
+    let right = match self.parse_logic() {
       Ok(right) => right,
-      Err(_)    => return self.parse_typecast_left(left),
+      Err(_)    => return self.parse_logic_left(left),
     };
     
     Ok(Expr{
       range: left.range.start..right.range.end,
-      ast: Node::new_assign(left.ast, right.ast),
+      ast: match left.ast.call() {
+        Some((name, params)) => Node::new_define(name, params.to_vec(), right.ast),
+        None => Node::new_assign(left.ast, right.ast),
+      },
+    })
+  }
+  
+  fn parse_logic(&mut self) -> Result<Expr, error::Error> {
+    match self.parse_not() {
+      Ok(left) => {
+        let left = self.parse_compare_left(left, 0)?;
+        self.parse_whole_left(left)
+      },
+      Err(err) => Err(err),
+    }
+  }
+  
+  fn parse_logic_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    let left = self.parse_typecast_left(left)?;
+    let left = self.parse_compare_left(left, 0)?;
+    self.parse_whole_left(left)
+  }
+  
+  /// Parse the remainder of 'x is 25% of what'. The words are too common
+  /// in prose to commit to early, so unless the whole phrase is present
+  /// nothing is consumed and `left` is produced unchanged.
+  fn parse_whole_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    let snapshot = self.scan.clone();
+    match self.parse_whole_phrase() {
+      Ok((pct, end)) => Ok(Expr{
+        range: left.range.start..end,
+        ast: Node::new_whole_of(left.ast, pct.ast),
+      }),
+      Err(_) => {
+        self.scan = snapshot;
+        Ok(left)
+      },
+    }
+  }
+  
+  fn parse_whole_phrase(&mut self) -> Result<(Expr, usize), error::Error> {
+    self.scan.discard(TType::Whitespace);
+    self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "is" })?;
+    self.scan.discard(TType::Whitespace);
+    let pct = self.parse_unary()?;
+    if !pct.ast.is_percent() {
+      return Err(error::Error::TokenNotMatched);
+    }
+    self.scan.discard(TType::Whitespace);
+    self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "of" })?;
+    self.scan.discard(TType::Whitespace);
+    let what = self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "what" })?;
+    Ok((pct, what.range.end))
+  }
+  
+  /// 'not' binds more loosely than comparisons, so 'not a > b' is
+  /// 'not (a > b)', but more tightly than 'and' and 'or'.
+  fn parse_not(&mut self) -> Result<Expr, error::Error> {
+    let op = match self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && tok.ttext == "not"
+    }) {
+      Ok(op) => op,
+      Err(_) => return self.parse_typecast(),
+    };
+    
+    let snapshot = self.scan.clone();
+    self.scan.discard(TType::Whitespace);
+    
+    let right = match self.scan.la_token() {
+      Some(tok) if Self::is_operand(tok) => self.parse_not()?,
+      _ => { // not followed by an operand, so just a word
+        self.scan = snapshot;
+        return self.parse_typecast_left(Expr{
+          range: op.range,
+          ast: Node::new_ident(&op.ttext),
+        });
+      },
+    };
+    let right = self.parse_compare_left(right, Self::logical_precedence("==").unwrap_or(0))?;
+    Ok(Expr{
+      range: op.range.start..right.range.end,
+      ast: Node::new_not(right.ast),
     })
   }
   
+  /// Parse the remainder of a comparison or logical expression by
+  /// precedence climbing, in the same way as parse_binary_left. The words
+  /// 'and' and 'or' are also common in prose, so they only join operands
+  /// when at least one side is itself a comparison or logical expression;
+  /// otherwise the word is left in the stream.
+  fn parse_compare_left(&mut self, left: Expr, prec: usize) -> Result<Expr, error::Error> {
+    let mut left = left;
+    loop {
+      let snapshot = self.scan.clone();
+      self.scan.discard(TType::Whitespace);
+      
+      let op = match self.scan.expect_token_fn(|tok| {
+        (tok.ttype == TType::Operator || tok.ttype == TType::Ident) && match Self::logical_precedence(&tok.ttext) {
+          Some(p) => p >= prec,
+          None    => false,
+        }
+      }) {
+        Ok(op) => op,
+        Err(_) => {
+          self.scan = snapshot;
+          return Ok(left);
+        },
+      };
+      
+      self.scan.discard(TType::Whitespace);
+      
+      let opp = Self::logical_precedence(&op.ttext).unwrap_or(prec);
+      let right = match self.scan.la_token() {
+        Some(tok) if Self::is_operand(tok) => match self.parse_not() {
+          Ok(right) => self.parse_compare_left(right, opp + 1),
+          Err(err)  => Err(err),
+        },
+        _ => Err(error::Error::TokenNotMatched),
+      };
+      let right = match right {
+        Ok(right) if op.ttype == TType::Operator || left.ast.is_boolean() || right.ast.is_boolean() => right,
+        _ => {
+          self.scan = snapshot;
+          return Ok(left);
+        },
+      };
+      
+      left = Self::logical(&op, left, right)?;
+    }
+  }
+  
+  fn logical_precedence(op: &str) -> Option<usize> {
+    match op {
+      "or" => Some(1),
+      "and" => Some(2),
+      "==" | "!=" | "<" | "<=" | ">" | ">=" => Some(3),
+      _ => None,
+    }
+  }
+  
+  fn logical(op: &scan::Token, left: Expr, right: Expr) -> Result<Expr, error::Error> {
+    let range = left.range.start..right.range.end;
+    let ast = match op.ttext.as_ref() {
+      "or"  => Node::new_or(left.ast, right.ast),
+      "and" => Node::new_and(left.ast, right.ast),
+      "=="  => Node::new_eq(left.ast, right.ast),
+      "!="  => Node::new_ne(left.ast, right.ast),
+      "<"   => Node::new_lt(left.ast, right.ast),
+      "<="  => Node::new_le(left.ast, right.ast),
+      ">"   => Node::new_gt(left.ast, right.ast),
+      ">="  => Node::new_ge(left.ast, right.ast),
+      _ => return Err(error::Error::TokenNotMatched),
+    };
+    Ok(Expr{
+      range: range,
+      ast: ast,
+    })
+  }
+  
+  /// Whether the token can begin an operand.
+  fn is_operand(tok: &scan::Token) -> bool {
+    match tok.ttype {
+      TType::Ident | TType::Number | TType::String | TType::LParen | TType::Currency => true,
+      TType::Operator => tok.ttext == "+" || tok.ttext == "-",
+      _ => false,
+    }
+  }
+  
   fn parse_typecast(&mut self) -> Result<Expr, error::Error> {
     match self.parse_arith() {
       Ok(left) => self.parse_typecast_left(left),
@@ -82,122 +319,220 @@ impl<'a> Parser<'a> {
     
     match self.scan.expect_token(TType::Typecast) {
       Ok(_)  => {},
-      Err(_) => return self.parse_arith_left(left),
+      Err(_) => {
+        // an operand that starts an expression, as 'g' does in 'g * 80 kg
+        // in N', may still be followed by arithmetic and then a cast
+        let left = self.parse_arith_left(left)?;
+        self.scan.discard(TType::Whitespace);
+        if let Some(TType::Typecast) = self.scan.la() {
+          return self.parse_typecast_left(left);
+        }
+        return Ok(left);
+      },
     };
     
     self.scan.discard(TType::Whitespace);
     
+    if let Ok(_) = self.scan.expect_token_fn(|tok| { tok.ttype == TType::Operator && tok.ttext == "%" }) {
+      return self.parse_percent_of_left(left);
+    }
+    
     let unit = match self.parse_unit() {
       Ok(unit) => unit,
-      Err(_)   => return Ok(left),
+      Err(_)   => match self.parse_format() {
+        Ok(format) => format,
+        Err(_)     => return Ok(left),
+      },
     };
     
     Ok(Expr{
       range: left.range.start..unit.range.end,
-      ast: Node::new_typecast(left.ast, unit.ast),
+      ast: Node::new_convert(left.ast, unit.ast),
+    })
+  }
+  
+  /// Parse the name of a display format, as in '63 in as packed', which
+  /// is cast to in the same way as a unit.
+  fn parse_format(&mut self) -> Result<Expr, error::Error> {
+    let tok = self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && value::Format::from(&tok.ttext).is_some()
+    })?;
+    Ok(Expr{
+      range: tok.range,
+      ast: Node::new_ident(&tok.ttext),
+    })
+  }
+  
+  /// Parse the remainder of '30 as % of 120' once '%' has been consumed.
+  fn parse_percent_of_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    self.scan.discard(TType::Whitespace);
+    self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "of" })?;
+    self.scan.discard(TType::Whitespace);
+    let right = self.parse_arith()?;
+    Ok(Expr{
+      range: left.range.start..right.range.end,
+      ast: Node::new_percent_of(left.ast, right.ast),
     })
   }
   
   fn parse_arith(&mut self) -> Result<Expr, error::Error> {
-    match self.parse_primary() {
+    match self.parse_unary() {
       Ok(left) => self.parse_arith_left(left),
       Err(err) => Err(err.into()),
     }
   }
   
   fn parse_arith_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    let left = self.parse_power_left(left)?;
+    self.parse_binary_left(left, 0)
+  }
+  
+  /// Parse the remainder of a binary expression by precedence climbing.
+  /// Operators that bind at least as tightly as `prec` are folded into
+  /// `left`; anything looser is left in the stream for the caller.
+  fn parse_binary_left(&mut self, left: Expr, prec: usize) -> Result<Expr, error::Error> {
+    let mut left = left;
+    loop {
+      self.scan.discard(TType::Whitespace);
+      
+      // 'of' only applies a percentage, anywhere else it is just a word
+      let op = match self.scan.expect_token_fn(|tok| {
+        (tok.ttype == TType::Operator || tok.ttype == TType::Ident) && match Self::precedence(&tok.ttext) {
+          Some(p) => p >= prec && (tok.ttext != "of" || left.ast.is_percent()),
+          None    => false,
+        }
+      }) {
+        Ok(op) => op,
+        Err(_) => return Ok(left),
+      };
+      
+      self.scan.discard(TType::Whitespace);
+      
+      let right = match self.scan.la_token() {
+        Some(tok) if Self::is_operand(tok) => self.parse_unary()?,
+        _ => return Ok(left),
+      };
+      
+      // all operators are left-associative, so the right operand only
+      // absorbs operators that bind more tightly than this one
+      let opp = Self::precedence(&op.ttext).unwrap_or(prec);
+      let right = self.parse_binary_left(right, opp + 1)?;
+      left = Self::binary(&op, left, right)?;
+    }
+  }
+  
+  fn precedence(op: &str) -> Option<usize> {
+    match op {
+      "mod" | "of" => return Some(2),
+      _ => {},
+    };
+    match op.chars().next() {
+      Some(scan::ADD) | Some(scan::SUB) => Some(1),
+      Some(scan::MUL) | Some(scan::DIV) => Some(2),
+      _ => None,
+    }
+  }
+  
+  fn binary(op: &scan::Token, left: Expr, right: Expr) -> Result<Expr, error::Error> {
+    let range = left.range.start..right.range.end;
+    let ast = match op.ttext.as_ref() {
+      "mod" => Node::new_mod(left.ast, right.ast),
+      "of"  => Node::new_of(left.ast, right.ast),
+      _ => match op.ttext.chars().next() {
+        Some(scan::ADD) => Node::new_add(left.ast, right.ast),
+        Some(scan::SUB) => Node::new_sub(left.ast, right.ast),
+        Some(scan::MUL) => Node::new_mul(left.ast, right.ast),
+        Some(scan::DIV) => Node::new_div(left.ast, right.ast),
+        _ => return Err(error::Error::TokenNotMatched),
+      },
+    };
+    Ok(Expr{
+      range: range,
+      ast: ast,
+    })
+  }
+  
+  fn parse_unary(&mut self) -> Result<Expr, error::Error> {
+    let op = match self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Operator && (tok.ttext == "+" || tok.ttext == "-")
+    }) {
+      Ok(op) => op,
+      Err(_) => return self.parse_power(),
+    };
+    
     self.scan.discard(TType::Whitespace);
     
-    let op = match self.scan.expect_token(TType::Operator) {
-      Ok(op) => op,
+    let right = self.parse_unary()?;
+    Ok(Expr{
+      range: op.range.start..right.range.end,
+      ast: match op.ttext.as_ref() {
+        "-" => Node::new_neg(right.ast),
+        _   => Node::new_pos(right.ast),
+      },
+    })
+  }
+  
+  fn parse_power(&mut self) -> Result<Expr, error::Error> {
+    match self.parse_primary() {
+      Ok(left) => self.parse_power_left(left),
+      Err(err) => Err(err),
+    }
+  }
+  
+  /// Exponentiation binds more tightly than unary operators on its left,
+  /// so '-2^2' is '-(2^2)', but its exponent may itself be signed, as in
+  /// '2^-1'. Recursing through parse_unary makes the operator
+  /// right-associative.
+  fn parse_power_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    self.scan.discard(TType::Whitespace);
+    
+    match self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Operator && (tok.ttext == "^" || tok.ttext == "**")
+    }) {
+      Ok(_)  => {},
       Err(_) => return Ok(left),
     };
     
     self.scan.discard(TType::Whitespace);
     
-    let ttype = match self.scan.la() {
-      Some(ttype) => ttype,
-      None => return Ok(left),
-    };
-    let right = match ttype {
-      TType::Verbatim => return Ok(left),
-      TType::End      => return Ok(left),
-      TType::Ident    => Some(self.parse_primary()?),
-      TType::Number   => Some(self.parse_primary()?),
-      TType::LParen   => Some(self.parse_primary()?),
-      _               => return Ok(left),
+    let right = match self.scan.la_token() {
+      Some(tok) if Self::is_operand(tok) => self.parse_unary()?,
+      _ => return Ok(left),
     };
     
-    let opc = op.ttext.chars().next().unwrap();
-    match right {
-      Some(right) => match opc {
-        scan::ADD => Ok(self.parse_arith_left(Expr{
-          range: left.range.start..right.range.end,
-          ast: Node::new_add(left.ast, right.ast)
-        })?),
-        scan::SUB => Ok(self.parse_arith_left(Expr{
-          range: left.range.start..right.range.end,
-          ast: Node::new_sub(left.ast, right.ast)
-        })?),
-        scan::MUL => Ok(self.parse_arith_left(Expr{
-          range: left.range.start..right.range.end,
-          ast: Node::new_mul(left.ast, right.ast)
-        })?),
-        scan::DIV => Ok(self.parse_arith_left(Expr{
-          range: left.range.start..right.range.end,
-          ast: Node::new_div(left.ast, right.ast)
-        })?),
-        scan::MOD => Ok(self.parse_arith_left(Expr{
-          range: left.range.start..right.range.end,
-          ast: Node::new_mod(left.ast, right.ast)
-        })?),
-        _ => Err(error::Error::TokenNotMatched),
-      },
-      None => {
-        let right = self.parse_arith()?;
-        match opc {
-          scan::ADD => Ok(Expr{
-            range: left.range.start..right.range.end,
-            ast: Node::new_add(left.ast, right.ast),
-          }),
-          scan::SUB => Ok(Expr{
-            range: left.range.start..right.range.end,
-            ast: Node::new_sub(left.ast, right.ast),
-          }),
-          scan::MUL => Ok(Expr{
-            range: left.range.start..right.range.end,
-            ast: Node::new_mul(left.ast, right.ast),
-          }),
-          scan::DIV => Ok(Expr{
-            range: left.range.start..right.range.end,
-            ast: Node::new_div(left.ast, right.ast),
-          }),
-          scan::MOD => Ok(Expr{
-            range: left.range.start..right.range.end,
-            ast: Node::new_mod(left.ast, right.ast),
-          }),
-          _ => Err(error::Error::TokenNotMatched),
-        }
-      },
-    }
+    Ok(Expr{
+      range: left.range.start..right.range.end,
+      ast: Node::new_pow(left.ast, right.ast),
+    })
   }
   
   fn parse_primary(&mut self) -> Result<Expr, error::Error> {
     let tok = self.scan.expect_token_fn(|tok| {
       tok.ttype == TType::Ident  ||
       tok.ttype == TType::Number ||
-      tok.ttype == TType::LParen
+      tok.ttype == TType::String ||
+      tok.ttype == TType::LParen ||
+      tok.ttype == TType::Currency
     })?;
+    if tok.ttype == TType::Currency {
+      return self.parse_currency_left(tok);
+    }
     
     let rng = tok.range.clone();
+    let number = tok.ttype == TType::Number;
     let exp = match &tok.ttype {
-      TType::Ident  => Expr{
+      TType::Ident if tok.ttext == "if" => return self.parse_if(tok),
+      TType::Ident  => self.parse_call_left(Expr{
         range: tok.range,
         ast: Node::new_ident(&tok.ttext),
-      },
+      })?,
       TType::Number => Expr{
+        ast: Node::new_number(tok.number()?),
+        range: tok.range,
+      },
+      TType::String => Expr{
         range: tok.range,
-        ast: Node::new_number(tok.ttext.parse::<f64>()?),
+        ast: Node::new_string(&tok.ttext),
       },
       TType::LParen => {
         let exp = self.parse_expr()?;
@@ -211,15 +546,249 @@ impl<'a> Parser<'a> {
     
     self.scan.discard(TType::Whitespace);
     
-    match self.parse_unit() {
-      Ok(unit) => Ok(Expr{
-        range: rng.start..unit.range.end,
-        ast: Node::new_typecast(exp.ast, unit.ast),
+    let unit = if number && self.is_ordinal(rng.end) {
+      Err(error::Error::TokenNotMatched) // as in '1st', which is not a stone
+    }else{
+      self.parse_unit_suffix()
+    };
+    
+    let exp = match unit {
+      Ok(unit) => {
+        let exp = self.parse_duration_left(Expr{
+          range: rng.start..unit.range.end,
+          ast: Node::new_typecast(exp.ast, unit.ast.clone()),
+        }, unit.ast);
+        self.parse_ingredient_left(exp)
+      },
+      Err(_) => if number {
+        self.parse_label_left(exp)
+      }else{
+        exp
+      },
+    };
+    
+    self.parse_percent_left(exp)
+  }
+  
+  /// Parse the amount following a currency symbol, as in '$100', which
+  /// is an amount in that currency.
+  fn parse_currency_left(&mut self, tok: scan::Token) -> Result<Expr, error::Error> {
+    let amount = self.parse_primary()?;
+    // the currency may start a compound unit, as in '$5/hour'
+    let unit = self.parse_compound_left(Expr{
+      range: tok.range.clone(),
+      ast: Self::currency(&tok)?,
+    });
+    Ok(Expr{
+      range: tok.range.start..cmp::max(amount.range.end, unit.range.end),
+      ast: Node::new_typecast(amount.ast, unit.ast),
+    })
+  }
+  
+  /// The currency a currency symbol stands for, as a unit.
+  fn currency(tok: &scan::Token) -> Result<Node, error::Error> {
+    match tok.ttext.chars().next().and_then(currency::symbol) {
+      Some(code) => Ok(Node::new_ident(code)),
+      None => Err(error::Error::TokenNotMatched),
+    }
+  }
+  
+  /// Whether an ordinal suffix follows the number ending at `end` with
+  /// no space between them.
+  fn is_ordinal(&mut self, end: usize) -> bool {
+    match self.scan.la_token() {
+      Some(tok) => tok.ttype == TType::Ident && tok.range.start == end && ORDINALS.contains(&tok.ttext.as_str()),
+      None => false,
+    }
+  }
+  
+  /// A word following a number that is neither a unit nor a keyword
+  /// labels it, as in '3 tasks', and is skipped. A variable, or a unit
+  /// written in a case that could mean more than one, as in '3 gb', is no
+  /// label but a unit that does not exist, so that the mistake is reported.
+  fn parse_label_left(&mut self, left: Expr) -> Expr {
+    let tok = match self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && !KEYWORDS.contains(&tok.ttext.as_str())
+    }) {
+      Ok(tok) => tok,
+      Err(_) => return left,
+    };
+    let ordinal = tok.range.start == left.range.end && ORDINALS.contains(&tok.ttext.as_str());
+    let unit = !ordinal && (
+      self.vars.contains(&tok.ttext) ||
+      !unit::Prefixed::from_any_case(&tok.ttext).is_empty() ||
+      unit::Compound::from_with(&tok.ttext.to_lowercase(), &self.units).is_some()
+    );
+    Expr{
+      range: left.range.start..tok.range.end,
+      ast: if unit {
+        Node::new_typecast(left.ast, Node::new_ident(&tok.ttext))
+      }else{
+        left.ast
+      },
+    }
+  }
+  
+  /// An ingredient following a quantity, as in '1 cup flour', lets the
+  /// quantity be converted between volume and mass.
+  fn parse_ingredient_left(&mut self, left: Expr) -> Expr {
+    let snapshot = self.scan.clone();
+    self.scan.discard(TType::Whitespace);
+    match self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && ingredient::Ingredient::from(&tok.ttext).is_some()
+    }) {
+      Ok(tok) => Expr{
+        range: left.range.start..tok.range.end,
+        ast: Node::new_ingredient(left.ast, Node::new_ident(&tok.ttext)),
+      },
+      Err(_) => {
+        self.scan = snapshot;
+        left
+      },
+    }
+  }
+  
+  /// Parse the remaining parts of a compound duration, as in '1h 30m' or
+  /// '2 days 4 hours', once its first part has been parsed. The parts are
+  /// summed in the unit of the first one.
+  fn parse_duration_left(&mut self, left: Expr, unit: Node) -> Expr {
+    if !Self::is_duration(unit.name().unwrap_or("")) {
+      return left;
+    }
+    let mut exp = left;
+    let mut parts = 1;
+    loop {
+      let snapshot = self.scan.clone();
+      match self.parse_duration_part() {
+        Ok(part) => exp = Expr{
+          range: exp.range.start..part.range.end,
+          ast: Node::new_add(exp.ast, part.ast),
+        },
+        Err(_) => {
+          self.scan = snapshot;
+          break;
+        },
+      };
+      parts += 1;
+    }
+    if parts == 1 {
+      exp
+    }else{
+      Expr{
+        range: exp.range,
+        ast: Node::new_typecast(exp.ast, unit),
+      }
+    }
+  }
+  
+  fn parse_duration_part(&mut self) -> Result<Expr, error::Error> {
+    self.scan.discard(TType::Whitespace);
+    let num = self.scan.expect_token_fn(|tok| { tok.ttype == TType::Number })?;
+    self.scan.discard(TType::Whitespace);
+    let unit = self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && (tok.ttext == "m" || Self::is_duration(&tok.ttext))
+    })?;
+    // 'm' is meters on its own, but minutes once it follows an hour
+    let name = if unit.ttext == "m" { "min" } else { &unit.ttext };
+    Ok(Expr{
+      range: num.range.start..unit.range.end,
+      ast: Node::new_typecast(Node::new_number(num.number()?), Node::new_ident(name)),
+    })
+  }
+  
+  fn is_duration(name: &str) -> bool {
+    match unit::Unit::from(name) {
+      Some(unit) => unit.dimension() == unit::Dimension::Time,
+      None => false,
+    }
+  }
+  
+  /// A '%' following an operand makes it a percentage; modulo is spelled
+  /// 'mod' instead.
+  fn parse_percent_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    self.scan.discard(TType::Whitespace);
+    
+    match self.scan.expect_token_fn(|tok| { tok.ttype == TType::Operator && tok.ttext == "%" }) {
+      Ok(tok) => Ok(Expr{
+        range: left.range.start..tok.range.end,
+        ast: Node::new_percent(left.ast),
+      }),
+      Err(_) => Ok(left),
+    }
+  }
+  
+  /// Parse the remainder of a conditional, 'if cond then a else b', once
+  /// 'if' has been consumed. When the rest of the conditional cannot be
+  /// parsed the word is treated as an ordinary identifier, since it is
+  /// just as likely to be prose.
+  fn parse_if(&mut self, tok: scan::Token) -> Result<Expr, error::Error> {
+    let snapshot = self.scan.clone();
+    match self.parse_if_branches() {
+      Ok((cond, then, other)) => Ok(Expr{
+        range: tok.range.start..other.range.end,
+        ast: Node::new_if(cond.ast, then.ast, other.ast),
       }),
-      Err(_) => Ok(exp),
+      Err(_) => {
+        self.scan = snapshot;
+        Ok(Expr{
+          range: tok.range,
+          ast: Node::new_ident(&tok.ttext),
+        })
+      },
     }
   }
   
+  fn parse_if_branches(&mut self) -> Result<(Expr, Expr, Expr), error::Error> {
+    self.scan.discard(TType::Whitespace);
+    let cond = self.parse_logic()?;
+    self.scan.discard(TType::Whitespace);
+    self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "then" })?;
+    self.scan.discard(TType::Whitespace);
+    let then = self.parse_logic()?;
+    self.scan.discard(TType::Whitespace);
+    self.scan.expect_token_fn(|tok| { tok.ttype == TType::Ident && tok.ttext == "else" })?;
+    self.scan.discard(TType::Whitespace);
+    let other = self.parse_logic()?;
+    Ok((cond, then, other))
+  }
+  
+  /// If the identifier `left` is immediately followed by an opening
+  /// parenthesis, parse a function call with the identifier as its name.
+  /// Otherwise `left` is produced unchanged.
+  fn parse_call_left(&mut self, left: Expr) -> Result<Expr, error::Error> {
+    match self.scan.expect_token(TType::LParen) {
+      Ok(_)  => {},
+      Err(_) => return Ok(left),
+    };
+    
+    let name = match left.ast.name() {
+      Some(name) => name.to_owned(),
+      None => return Err(error::Error::TokenNotMatched),
+    };
+    
+    let mut args: Vec<Node> = Vec::new();
+    self.scan.discard(TType::Whitespace);
+    let end = match self.scan.expect_token(TType::RParen) {
+      Ok(tok) => tok.range.end,
+      Err(_)  => loop {
+        args.push(self.parse_typecast()?.ast);
+        self.scan.discard(TType::Whitespace);
+        let tok = self.scan.expect_token_fn(|tok| {
+          tok.ttype == TType::Comma || tok.ttype == TType::RParen
+        })?;
+        if tok.ttype == TType::RParen {
+          break tok.range.end;
+        }
+        self.scan.discard(TType::Whitespace);
+      },
+    };
+    
+    Ok(Expr{
+      range: left.range.start..end,
+      ast: Node::new_call(&name, args),
+    })
+  }
+  
   fn parse_expr(&mut self) -> Result<Expr, error::Error> {
     let expr = self.parse_enter()?;
     let tok = self.scan.expect_token(TType::RParen)?;
@@ -230,27 +799,133 @@ impl<'a> Parser<'a> {
   }
   
   fn parse_ident(&mut self) -> Result<Expr, error::Error> {
-    let tok = self.scan.expect_token(TType::Ident)?;
+    let tok = self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && tok.ttext != "if" && tok.ttext != "not"
+    })?;
     Ok(Expr{
       range: tok.range,
       ast: Node::new_ident(&tok.ttext),
     })
   }
   
+  /// Parse a unit that follows a value, as in '5 in'. Since 'in' is also
+  /// the conversion operator, it is only taken as inches when it is not
+  /// followed by another unit, as in '5 in cm'. A word following a value
+  /// is a unit even when a variable has the same name, so '100 kg' is a
+  /// mass whatever 'kg' is; anywhere else the variable is used instead.
+  fn parse_unit_suffix(&mut self) -> Result<Expr, error::Error> {
+    let snapshot = self.scan.clone();
+    let unit = self.parse_unit()?;
+    if unit.ast.name() == Some("in") {
+      self.scan.discard(TType::Whitespace);
+      if let Some(TType::Ident | TType::Currency) = self.scan.la() {
+        self.scan = snapshot;
+        return Err(error::Error::TokenNotMatched);
+      }
+    }
+    Ok(unit)
+  }
+  
   fn parse_unit(&mut self) -> Result<Expr, error::Error> {
+    let snapshot = self.scan.clone();
+    let units = &self.units;
     let tok = self.scan.expect_token_fn(|tok| {
-      tok.ttype == TType::Ident && if let Some(_) = unit::Unit::from(&tok.ttext) { true } else { false }
+      match tok.ttype {
+        TType::Ident    => tok.ttext == "nautical" || unit::Compound::from_with(&tok.ttext, units).is_some(),
+        TType::Typecast => tok.ttext == "in", // inches
+        TType::Currency => true, // as in '50 €' or 'in $'
+        _               => false,
+      }
     })?;
-    Ok(Expr{
-      range: tok.range,
-      ast: Node::new_ident(&tok.ttext),
-    })
+    if tok.ttype == TType::Currency {
+      return Ok(self.parse_compound_left(Expr{
+        range: tok.range.clone(),
+        ast: Self::currency(&tok)?,
+      }));
+    }
+    if tok.ttext != "nautical" {
+      return Ok(self.parse_compound_left(Expr{
+        range: tok.range,
+        ast: Node::new_ident(&tok.ttext),
+      }));
+    }
+    
+    self.scan.discard(TType::Whitespace);
+    
+    match self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Ident && (tok.ttext == "mile" || tok.ttext == "miles")
+    }) {
+      Ok(end) => Ok(Expr{
+        range: tok.range.start..end.range.end,
+        ast: Node::new_ident("nmi"),
+      }),
+      Err(err) => {
+        self.scan = snapshot;
+        Err(err)
+      },
+    }
+  }
+  
+  /// Parse the remaining terms of a compound unit, as in 'km/h', 'm^2' or
+  /// 'kg*m/s^2', once its first unit has been parsed. Terms are written
+  /// without whitespace between them, so that '100 km / 2 h' remains a
+  /// division. The compound is produced as a single identifier.
+  fn parse_compound_left(&mut self, first: Expr) -> Expr {
+    let mut name = first.ast.name().unwrap_or_default().to_string();
+    let mut range = first.range;
+    loop {
+      let snapshot = self.scan.clone();
+      match self.parse_compound_term() {
+        Ok((text, end)) => {
+          name.push_str(&text);
+          range.end = end;
+        },
+        Err(_) => {
+          self.scan = snapshot;
+          break;
+        },
+      }
+    }
+    Expr{
+      range: range,
+      ast: Node::new_ident(&name),
+    }
+  }
+  
+  fn parse_compound_term(&mut self) -> Result<(String, usize), error::Error> {
+    let op = self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Operator && (tok.ttext == "/" || tok.ttext == "*" || tok.ttext == "^" || tok.ttext == "**")
+    })?;
+    if op.ttext == "/" || op.ttext == "*" {
+      let units = &self.units;
+      let unit = self.scan.expect_token_fn(|tok| {
+        match tok.ttype {
+          TType::Ident    => unit::Compound::from_with(&tok.ttext, units).is_some(),
+          TType::Currency => true, // as in 'kWh/$'
+          _               => false,
+        }
+      })?;
+      let name = match unit.ttype {
+        TType::Currency => Self::currency(&unit)?.to_string(),
+        _ => unit.ttext,
+      };
+      return Ok((format!("{}{}", op.ttext, name), unit.range.end));
+    }
+    let sign = match self.scan.expect_token_fn(|tok| { tok.ttype == TType::Operator && tok.ttext == "-" }) {
+      Ok(_) => "-",
+      Err(_) => "",
+    };
+    let exp = self.scan.expect_token_fn(|tok| {
+      tok.ttype == TType::Number && tok.ttext.chars().all(|c| { c.is_ascii_digit() })
+    })?;
+    Ok((format!("^{}{}", sign, exp.ttext), exp.range.end))
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::rdl::value;
   
   fn parse_expr(t: &str) -> Result<Node, error::Error> {
     let e = Parser::new(Scanner::new(t)).parse()?;
@@ -258,14 +933,14 @@ mod tests {
     Ok(e.ast)
   }
   
-  fn exec_node(n: Node, cxt: &mut Context) -> Result<unit::Value, error::Error> {
+  fn exec_node(n: Node, cxt: &mut Context) -> Result<value::Value, error::Error> {
     let v = n.exec(cxt)?;
     println!("=== [{}] → {}", n, v);
     Ok(v)
   }
   
   fn exec_line(text: &str, cxt: &mut Context) -> String {
-    let (_, res) = rdl::render_with_options(cxt, text, 0, 0, None, Some(&rdl::Options{verbose: true, debug: false}));
+    let (_, res) = rdl::render_with_options(cxt, text, 0, 0, None, Some(&rdl::Options{verbose: true, debug: false, packed: false}));
     println!("*** [{}] → [{}]", text, res.text());
     res.text().to_owned()
   }
@@ -273,25 +948,25 @@ mod tests {
   #[test]
   fn parse_primitive() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = parse_expr(r#"1"#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1.0"#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"123.456"#).expect("Could not parse");
     assert_eq!(Node::new_number(123.456), n);
-    assert_eq!(Ok(unit::Value::raw(123.456)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(123.456)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"a"#).expect("Could not parse");
     assert_eq!(Node::new_ident("a"), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"Hello"#).expect("Could not parse");
     assert_eq!(Node::new_ident("Hello"), n);
@@ -301,169 +976,923 @@ mod tests {
   #[test]
   fn parse_ws() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = parse_expr(r#"  1"#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1  "#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"  1  "#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
   }
   
   #[test]
   fn parse_arith() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = parse_expr(r#"1 + 2"#).expect("Could not parse");
     assert_eq!(Node::new_add(Node::new_number(1.0), Node::new_number(2.0)), n);
-    assert_eq!(Ok(unit::Value::raw(3.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 - 2"#).expect("Could not parse");
     assert_eq!(Node::new_sub(Node::new_number(1.0), Node::new_number(2.0)), n);
-    assert_eq!(Ok(unit::Value::raw(-1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(-1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 * 2"#).expect("Could not parse");
     assert_eq!(Node::new_mul(Node::new_number(1.0), Node::new_number(2.0)), n);
-    assert_eq!(Ok(unit::Value::raw(2.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(2.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 / 2"#).expect("Could not parse");
     assert_eq!(Node::new_div(Node::new_number(1.0), Node::new_number(2.0)), n);
-    assert_eq!(Ok(unit::Value::raw(0.5)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(0.5)), exec_node(n, &mut cxt));
     
-    let n = parse_expr(r#"4 % 3"#).expect("Could not parse");
+    let n = parse_expr(r#"4 mod 3"#).expect("Could not parse");
     assert_eq!(Node::new_mod(Node::new_number(4.0), Node::new_number(3.0)), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"a + 2"#).expect("Could not parse");
     assert_eq!(Node::new_add(Node::new_ident("a"), Node::new_number(2.0)), n);
-    assert_eq!(Ok(unit::Value::raw(3.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 + b"#).expect("Could not parse");
     assert_eq!(Node::new_add(Node::new_number(1.0), Node::new_ident("b")), n);
-    assert_eq!(Ok(unit::Value::raw(3.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"a + b"#).expect("Could not parse");
     assert_eq!(Node::new_add(Node::new_ident("a"), Node::new_ident("b")), n);
-    assert_eq!(Ok(unit::Value::raw(3.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
   }
   
   #[test]
   fn parse_subexpr() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = parse_expr(r#"(1)"#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"(a)"#).expect("Could not parse");
     assert_eq!(Node::new_ident("a"), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"((a))"#).expect("Could not parse");
     assert_eq!(Node::new_ident("a"), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"(1 + 2)"#).expect("Could not parse");
     assert_eq!(Node::new_add(Node::new_number(1.0), Node::new_number(2.0)), n);
-    assert_eq!(Ok(unit::Value::raw(3.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 - 2 + 3"#).expect("Could not parse");
     assert_eq!(Node::new_add(Node::new_sub(Node::new_number(1.0), Node::new_number(2.0)), Node::new_number(3.0)), n);
-    assert_eq!(Ok(unit::Value::raw(2.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(2.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 - (2 + 3)"#).expect("Could not parse");
     assert_eq!(Node::new_sub(Node::new_number(1.0), Node::new_add(Node::new_number(2.0), Node::new_number(3.0))), n);
-    assert_eq!(Ok(unit::Value::raw(-4.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(-4.0)), n.exec(&mut cxt));
     
     let n = parse_expr(r#"1 - (2 + 3) / 4"#).expect("Could not parse");
-    assert_eq!(Node::new_div(Node::new_sub(Node::new_number(1.0), Node::new_add(Node::new_number(2.0), Node::new_number(3.0))), Node::new_number(4.0)), n);
-    assert_eq!(Ok(unit::Value::raw(-1.0)), n.exec(&mut cxt));
+    assert_eq!(Node::new_sub(Node::new_number(1.0), Node::new_div(Node::new_add(Node::new_number(2.0), Node::new_number(3.0)), Node::new_number(4.0))), n);
+    assert_eq!(Ok(value::Value::raw(-0.25)), n.exec(&mut cxt));
     
     let n = parse_expr(r#"1 - ((5 + 3) / 4)"#).expect("Could not parse");
     assert_eq!(Node::new_sub(Node::new_number(1.0), Node::new_div(Node::new_add(Node::new_number(5.0), Node::new_number(3.0)), Node::new_number(4.0))), n);
-    assert_eq!(Ok(unit::Value::raw(-1.0)), n.exec(&mut cxt));
+    assert_eq!(Ok(value::Value::raw(-1.0)), n.exec(&mut cxt));
+  }
+  
+  #[test]
+  fn parse_precedence() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"100 + 5 * 2"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_number(100.0), Node::new_mul(Node::new_number(5.0), Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::raw(110.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 * 3 + 4"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_mul(Node::new_number(2.0), Node::new_number(3.0)), Node::new_number(4.0)), n);
+    assert_eq!(Ok(value::Value::raw(10.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 + 6 / 3 - 2"#).expect("Could not parse");
+    assert_eq!(Node::new_sub(Node::new_add(Node::new_number(1.0), Node::new_div(Node::new_number(6.0), Node::new_number(3.0))), Node::new_number(2.0)), n);
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"8 - 3 - 2"#).expect("Could not parse");
+    assert_eq!(Node::new_sub(Node::new_sub(Node::new_number(8.0), Node::new_number(3.0)), Node::new_number(2.0)), n);
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"16 / 4 / 2"#).expect("Could not parse");
+    assert_eq!(Node::new_div(Node::new_div(Node::new_number(16.0), Node::new_number(4.0)), Node::new_number(2.0)), n);
+    assert_eq!(Ok(value::Value::raw(2.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"7 - 9 mod 4 * 2"#).expect("Could not parse");
+    assert_eq!(Node::new_sub(Node::new_number(7.0), Node::new_mul(Node::new_mod(Node::new_number(9.0), Node::new_number(4.0)), Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::raw(5.0)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_unary() {
+    let mut cxt = Context::new();
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    
+    let n = parse_expr(r#"-5"#).expect("Could not parse");
+    assert_eq!(Node::new_neg(Node::new_number(5.0)), n);
+    assert_eq!(Ok(value::Value::raw(-5.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"+5"#).expect("Could not parse");
+    assert_eq!(Node::new_pos(Node::new_number(5.0)), n);
+    assert_eq!(Ok(value::Value::raw(5.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"-a"#).expect("Could not parse");
+    assert_eq!(Node::new_neg(Node::new_ident("a")), n);
+    assert_eq!(Ok(value::Value::raw(-1.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"-(a + b)"#).expect("Could not parse");
+    assert_eq!(Node::new_neg(Node::new_add(Node::new_ident("a"), Node::new_ident("b"))), n);
+    assert_eq!(Ok(value::Value::raw(-3.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"--a"#).expect("Could not parse");
+    assert_eq!(Node::new_neg(Node::new_neg(Node::new_ident("a"))), n);
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"3 * -2"#).expect("Could not parse");
+    assert_eq!(Node::new_mul(Node::new_number(3.0), Node::new_neg(Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::raw(-6.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"-2 * 3 + 1"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_mul(Node::new_neg(Node::new_number(2.0)), Node::new_number(3.0)), Node::new_number(1.0)), n);
+    assert_eq!(Ok(value::Value::raw(-5.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a - -b"#).expect("Could not parse");
+    assert_eq!(Node::new_sub(Node::new_ident("a"), Node::new_neg(Node::new_ident("b"))), n);
+    assert_eq!(Ok(value::Value::raw(3.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"-3 kg"#).expect("Could not parse");
+    assert_eq!(Node::new_neg(Node::new_typecast(Node::new_number(3.0), Node::new_ident("kg"))), n);
+    assert_eq!(Ok(value::Value::new(-3.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"x = -a"#).expect("Could not parse");
+    assert_eq!(Node::new_assign(Node::new_ident("x"), Node::new_neg(Node::new_ident("a"))), n);
+    assert_eq!(Ok(value::Value::raw(-1.0)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_power() {
+    let mut cxt = Context::new();
+    cxt.set("a", value::Value::raw(2.0));
+    
+    let n = parse_expr(r#"2 ^ 3"#).expect("Could not parse");
+    assert_eq!(Node::new_pow(Node::new_number(2.0), Node::new_number(3.0)), n);
+    assert_eq!(Ok(value::Value::raw(8.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 ** 3"#).expect("Could not parse");
+    assert_eq!(Node::new_pow(Node::new_number(2.0), Node::new_number(3.0)), n);
+    assert_eq!(Ok(value::Value::raw(8.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 ^ 3 ^ 2"#).expect("Could not parse");
+    assert_eq!(Node::new_pow(Node::new_number(2.0), Node::new_pow(Node::new_number(3.0), Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::raw(512.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 * 3 ^ 2"#).expect("Could not parse");
+    assert_eq!(Node::new_mul(Node::new_number(2.0), Node::new_pow(Node::new_number(3.0), Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::raw(18.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"-2 ^ 2"#).expect("Could not parse");
+    assert_eq!(Node::new_neg(Node::new_pow(Node::new_number(2.0), Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::raw(-4.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 ^ -1"#).expect("Could not parse");
+    assert_eq!(Node::new_pow(Node::new_number(2.0), Node::new_neg(Node::new_number(1.0))), n);
+    assert_eq!(Ok(value::Value::raw(0.5)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a ^ 2 + 1"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_pow(Node::new_ident("a"), Node::new_number(2.0)), Node::new_number(1.0)), n);
+    assert_eq!(Ok(value::Value::raw(5.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(3 kg) ^ 1"#).expect("Could not parse");
+    assert_eq!(Node::new_pow(Node::new_typecast(Node::new_number(3.0), Node::new_ident("kg")), Node::new_number(1.0)), n);
+    assert_eq!(Ok(value::Value::new(3.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(3 kg) ^ 2"#).expect("Could not parse");
+    assert_eq!(Node::new_pow(Node::new_typecast(Node::new_number(3.0), Node::new_ident("kg")), Node::new_number(2.0)), n);
+    assert_eq!(Ok(value::Value::Number(unit::Value::option(9.0, unit::Compound::from("kg²")))), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(3 kg) ^ 0.5"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
+  }
+  
+  #[test]
+  fn parse_compare() {
+    let mut cxt = Context::new();
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    
+    let n = parse_expr(r#"a < b"#).expect("Could not parse");
+    assert_eq!(Node::new_lt(Node::new_ident("a"), Node::new_ident("b")), n);
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a + 1 >= b * 2"#).expect("Could not parse");
+    assert_eq!(Node::new_ge(Node::new_add(Node::new_ident("a"), Node::new_number(1.0)), Node::new_mul(Node::new_ident("b"), Node::new_number(2.0))), n);
+    assert_eq!(Ok(value::Value::Boolean(false)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a+1==b"#).expect("Could not parse");
+    assert_eq!(Node::new_eq(Node::new_add(Node::new_ident("a"), Node::new_number(1.0)), Node::new_ident("b")), n);
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a != -b"#).expect("Could not parse");
+    assert_eq!(Node::new_ne(Node::new_ident("a"), Node::new_neg(Node::new_ident("b"))), n);
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 kg > 20 g"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 kg in g <= 999"#).expect("Could not parse");
+    assert_eq!(Node::new_le(Node::new_convert(Node::new_typecast(Node::new_number(1.0), Node::new_ident("kg")), Node::new_ident("g")), Node::new_number(999.0)), n);
+    assert_eq!(Ok(value::Value::Boolean(false)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 cup < 1 kg"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
+    
+    let n = parse_expr(r#"c = a > b"#).expect("Could not parse");
+    assert_eq!(Node::new_assign(Node::new_ident("c"), Node::new_gt(Node::new_ident("a"), Node::new_ident("b"))), n);
+    assert_eq!(Ok(value::Value::Boolean(false)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"c + 1"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err()); // no arithmetic on booleans
+  }
+  
+  #[test]
+  fn parse_logic() {
+    let mut cxt = Context::new();
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    
+    let n = parse_expr(r#"a < b and b < 3"#).expect("Could not parse");
+    assert_eq!(Node::new_and(Node::new_lt(Node::new_ident("a"), Node::new_ident("b")), Node::new_lt(Node::new_ident("b"), Node::new_number(3.0))), n);
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a > b or a == 1 and b == 1"#).expect("Could not parse");
+    assert_eq!(Node::new_or(Node::new_gt(Node::new_ident("a"), Node::new_ident("b")), Node::new_and(Node::new_eq(Node::new_ident("a"), Node::new_number(1.0)), Node::new_eq(Node::new_ident("b"), Node::new_number(1.0)))), n);
+    assert_eq!(Ok(value::Value::Boolean(false)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"not a > b"#).expect("Could not parse");
+    assert_eq!(Node::new_not(Node::new_gt(Node::new_ident("a"), Node::new_ident("b"))), n);
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"not a > b and not (b > a)"#).expect("Could not parse");
+    assert_eq!(Node::new_and(Node::new_not(Node::new_gt(Node::new_ident("a"), Node::new_ident("b"))), Node::new_not(Node::new_gt(Node::new_ident("b"), Node::new_ident("a")))), n);
+    assert_eq!(Ok(value::Value::Boolean(false)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(a < b) or nope"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt)); // short-circuits
+    
+    let n = parse_expr(r#"a and b"#).expect("Could not parse");
+    assert_eq!(Node::new_ident("a"), n); // just prose, neither side is a condition
+    
+    let n = parse_expr(r#"not, really"#).expect("Could not parse");
+    assert_eq!(Node::new_ident("not"), n);
+  }
+  
+  #[test]
+  fn parse_if() {
+    let mut cxt = Context::new();
+    cxt.set("weight", value::Value::new(25.0, unit::Unit::Kilogram));
+    
+    let n = parse_expr(r#"shipping = if weight > 20 kg then 15 else 8"#).expect("Could not parse");
+    assert_eq!(Node::new_assign(Node::new_ident("shipping"), Node::new_if(Node::new_gt(Node::new_ident("weight"), Node::new_typecast(Node::new_number(20.0), Node::new_ident("kg"))), Node::new_number(15.0), Node::new_number(8.0))), n);
+    assert_eq!(Ok(value::Value::raw(15.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"if weight < 20000 g then 1 else if weight < 30 kg then 2 else 3"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::raw(2.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 * (if weight > 1 kg and weight < 2 kg then 1 else 10) + 1"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::raw(21.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"if weight then 1 else 2"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err()); // the condition must be boolean
+    
+    let n = parse_expr(r#"if you like"#).expect("Could not parse");
+    assert_eq!(Node::new_ident("if"), n);
+  }
+  
+  #[test]
+  fn parse_percent() {
+    let mut cxt = Context::new();
+    cxt.set("x", value::Value::raw(30.0));
+    
+    let n = parse_expr(r#"20%"#).expect("Could not parse");
+    assert_eq!(Node::new_percent(Node::new_number(20.0)), n);
+    assert_eq!(Ok(value::Value::Percent(20.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"20% of 150 + 10"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_of(Node::new_percent(Node::new_number(20.0)), Node::new_number(150.0)), Node::new_number(10.0)), n);
+    assert_eq!(Ok(value::Value::raw(40.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"150 + 10%"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_number(150.0), Node::new_percent(Node::new_number(10.0))), n);
+    assert_eq!(Ok(value::Value::raw(165.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"150 - 15%"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::raw(127.5)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 kg + 10 %"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(2.2, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"30 as % of 120"#).expect("Could not parse");
+    assert_eq!(Node::new_percent_of(Node::new_number(30.0), Node::new_number(120.0)), n);
+    assert_eq!(Ok(value::Value::Percent(25.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"250 g as % of 2 kg"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::Percent(12.5)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"x is 25% of what"#).expect("Could not parse");
+    assert_eq!(Node::new_whole_of(Node::new_ident("x"), Node::new_percent(Node::new_number(25.0))), n);
+    assert_eq!(Ok(value::Value::raw(120.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"x mod 7"#).expect("Could not parse");
+    assert_eq!(Node::new_mod(Node::new_ident("x"), Node::new_number(7.0)), n);
+    assert_eq!(Ok(value::Value::raw(2.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"x of 7"#).expect("Could not parse");
+    assert_eq!(Node::new_ident("x"), n); // only a percentage takes 'of'
+    
+    let n = parse_expr(r#"5 kg%"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
+  }
+  
+  #[test]
+  fn parse_string() {
+    let mut cxt = Context::new();
+    cxt.set("total", value::Value::new(2.5, unit::Unit::Kilogram));
+    
+    let n = parse_expr(r#""Hello""#).expect("Could not parse");
+    assert_eq!(Node::new_string("Hello"), n);
+    assert_eq!(Ok(value::Value::String("Hello".to_string())), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"label = "Total: " + total"#).expect("Could not parse");
+    assert_eq!(Node::new_assign(Node::new_ident("label"), Node::new_add(Node::new_string("Total: "), Node::new_ident("total"))), n);
+    assert_eq!(Ok(value::Value::String("Total: 2.5 kg".to_string())), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"label + ", " + 10% + " \"off\"""#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::String("Total: 2.5 kg, 10% \"off\"".to_string())), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"if total > 2 kg then "heavy" else "light""#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::String("heavy".to_string())), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#""a" == "a""#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::Boolean(true)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#""2" * 3"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
+  }
+  
+  #[test]
+  fn parse_call() {
+    let mut cxt = Context::new_with_stdlib();
+    cxt.set("a", value::Value::raw(16.0));
+    
+    let n = parse_expr(r#"sqrt(a)"#).expect("Could not parse");
+    assert_eq!(Node::new_call("sqrt", vec![Node::new_ident("a")]), n);
+    assert_eq!(Ok(value::Value::raw(4.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"max(1, a, 3)"#).expect("Could not parse");
+    assert_eq!(Node::new_call("max", vec![Node::new_number(1.0), Node::new_ident("a"), Node::new_number(3.0)]), n);
+    assert_eq!(Ok(value::Value::raw(16.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"min( 2 + 3 , a / 2 )"#).expect("Could not parse");
+    assert_eq!(Node::new_call("min", vec![Node::new_add(Node::new_number(2.0), Node::new_number(3.0)), Node::new_div(Node::new_ident("a"), Node::new_number(2.0))]), n);
+    assert_eq!(Ok(value::Value::raw(5.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 + abs(-2) * 3"#).expect("Could not parse");
+    assert_eq!(Node::new_add(Node::new_number(1.0), Node::new_mul(Node::new_call("abs", vec![Node::new_neg(Node::new_number(2.0))]), Node::new_number(3.0))), n);
+    assert_eq!(Ok(value::Value::raw(7.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"round(sqrt(2) * 10)"#).expect("Could not parse");
+    assert_eq!(Node::new_call("round", vec![Node::new_mul(Node::new_call("sqrt", vec![Node::new_number(2.0)]), Node::new_number(10.0))]), n);
+    assert_eq!(Ok(value::Value::raw(14.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"max(1 cup, 20 tbsp)"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(1.25, unit::Unit::Cup)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"b = floor(2.5 kg)"#).expect("Could not parse");
+    assert_eq!(Node::new_assign(Node::new_ident("b"), Node::new_call("floor", vec![Node::new_typecast(Node::new_number(2.5), Node::new_ident("kg"))])), n);
+    assert_eq!(Ok(value::Value::new(2.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"sin(1 kg)"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
+    
+    let n = parse_expr(r#"nope(1)"#).expect("Could not parse");
+    assert_eq!(Err(error::Error::UnboundFunction("nope".to_string())), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"a (1)"#).expect("Could not parse");
+    assert_eq!(Node::new_ident("a"), n); // not a call; the name must be adjacent
+  }
+  
+  #[test]
+  fn parse_define() {
+    let mut cxt = Context::new();
+    cxt.set("price", value::Value::raw(1.0));
+    
+    let n = parse_expr(r#"margin(price, cost) = (price - cost) / price"#).expect("Could not parse");
+    assert_eq!(Node::new_define("margin", vec![Node::new_ident("price"), Node::new_ident("cost")], Node::new_div(Node::new_sub(Node::new_ident("price"), Node::new_ident("cost")), Node::new_ident("price"))), n);
+    assert_eq!(Err(error::Error::NoValue), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"margin(200, 150)"#).expect("Could not parse");
+    assert_eq!(Node::new_call("margin", vec![Node::new_number(200.0), Node::new_number(150.0)]), n);
+    assert_eq!(Ok(value::Value::raw(0.25)), exec_node(n, &mut cxt));
+    assert_eq!(Some(value::Value::raw(1.0)), cxt.get("price")); // parameters do not leak
+    
+    let n = parse_expr(r#"markup(p, c) = margin(p, c) * 100"#).expect("Could not parse");
+    assert_eq!(Err(error::Error::NoValue), exec_node(n, &mut cxt));
+    let n = parse_expr(r#"markup(10, 5) + 1"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::raw(51.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"zero() = 0"#).expect("Could not parse");
+    assert_eq!(Node::new_define("zero", vec![], Node::new_number(0.0)), n);
+    assert_eq!(Err(error::Error::NoValue), exec_node(n, &mut cxt));
+    let n = parse_expr(r#"zero()"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::raw(0.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"bad(1) = 0"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
+    assert!(cxt.func("bad").is_none());
+    
+    let n = parse_expr(r#"loop(x) = loop(x)"#).expect("Could not parse");
+    assert_eq!(Err(error::Error::NoValue), exec_node(n, &mut cxt));
+    let n = parse_expr(r#"loop(1)"#).expect("Could not parse");
+    assert!(exec_node(n, &mut cxt).is_err());
   }
   
   #[test]
   fn parse_assign() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = parse_expr(r#"d = 100"#).expect("Could not parse");
     assert_eq!(Node::new_assign(Node::new_ident("d"), Node::new_number(100.0)), n);
-    assert_eq!(Ok(unit::Value::raw(100.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(100.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"d"#).expect("Could not parse");
     assert_eq!(Node::new_ident("d"), n); // value is now set for 'd'
-    assert_eq!(Ok(unit::Value::raw(100.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(100.0)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_length() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"5 km in mi"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(5.0), Node::new_ident("km")), Node::new_ident("mi")), n);
+    assert_eq!(Ok(value::Value::new(3.10685596119, unit::Unit::Mile)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"6 ft + 20 cm in cm"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(202.88, unit::Unit::Centimeter)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"12 in"#).expect("Could not parse");
+    assert_eq!(Node::new_typecast(Node::new_number(12.0), Node::new_ident("in")), n);
+    assert_eq!(Ok(value::Value::new(12.0, unit::Unit::Inch)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"12 in in ft"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(12.0), Node::new_ident("in")), Node::new_ident("ft")), n);
+    assert_eq!(Ok(value::Value::new(1.0, unit::Unit::Foot)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 in + 1 cm"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(6.08, unit::Unit::Centimeter)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 yard in in"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(36.0, unit::Unit::Inch)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"3 feet + 2 inches"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(38.0, unit::Unit::Inch)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"10 nautical miles in kilometres"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(10.0), Node::new_ident("nmi")), Node::new_ident("kilometres")), n);
+    assert_eq!(Ok(value::Value::new(18.52, unit::Unit::Kilometer)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"5 in the box"#).expect("Could not parse");
+    assert_eq!(Node::new_number(5.0), n);
+    
+    let n = parse_expr(r#"3 nautical"#).expect("Could not parse");
+    assert_eq!(Node::new_number(3.0), n);
+  }
+  
+  #[test]
+  fn parse_temperature() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"350 F in C"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(350.0), Node::new_ident("F")), Node::new_ident("C")), n);
+    assert_eq!(Ok(value::Value::new(176.666666667, unit::Unit::Celsius)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"20 C + 5 C"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(25.0, unit::Unit::Celsius)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"20°C + 9°F"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(25.0, unit::Unit::Celsius)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"100 celsius in fahrenheit"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(212.0, unit::Unit::Fahrenheit)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"0 K in °C"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(-273.15, unit::Unit::Celsius)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_duration() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"1h 30m"#).expect("Could not parse");
+    assert_eq!(Node::new_typecast(Node::new_add(Node::new_typecast(Node::new_number(1.0), Node::new_ident("h")), Node::new_typecast(Node::new_number(30.0), Node::new_ident("min"))), Node::new_ident("h")), n);
+    assert_eq!(Ok(value::Value::new(1.5, unit::Unit::Hour)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 days 4 hours"#).expect("Could not parse");
+    assert_eq!("2 days 4 hours", exec_node(n, &mut cxt).expect("Could not exec").to_string());
+    
+    let n = parse_expr(r#"1 hour 30 minutes 36 s in min"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(90.6, unit::Unit::Minute)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(3 tasks * 45 min) in hours"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_mul(Node::new_number(3.0), Node::new_typecast(Node::new_number(45.0), Node::new_ident("min"))), Node::new_ident("hours")), n);
+    let v = exec_node(n, &mut cxt).expect("Could not exec");
+    assert_eq!(value::Value::new(2.25, unit::Unit::Hour), v);
+    assert_eq!("2 hours 15 min", v.to_string());
+    assert_eq!("hours((3 * min(45))) → 2.25 hours", &exec_line("(3 tasks * 45 min) in hours", &mut cxt));
+    assert_eq!("days(month(1)) → 30.436875 days", &exec_line("1 month in days", &mut cxt));
+    assert_eq!("hours(days((days(2) + hours(4)))) → 52 hours", &exec_line("2 days 4 hours in hours", &mut cxt));
+    assert_eq!("days((days(2) + hours(4))) → 2 days 4 hours", &exec_line("2 days 4 hours", &mut cxt));
+    
+    let n = parse_expr(r#"3 m"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(3.0, unit::Unit::Meter)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 h 5"#).expect("Could not parse");
+    assert_eq!(Node::new_typecast(Node::new_number(2.0), Node::new_ident("h")), n);
+    
+    let n = parse_expr(r#"12 apples mod 5"#).expect("Could not parse");
+    assert_eq!(Node::new_mod(Node::new_number(12.0), Node::new_number(5.0)), n);
+  }
+  
+  #[test]
+  fn parse_data() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"2 TB / 100 Mbps in hours"#).expect("Could not parse");
+    let v = exec_node(n, &mut cxt).expect("Could not exec");
+    assert_eq!(value::Value::new(44.4444444444, unit::Unit::Hour), v);
+    assert_eq!("44 hours 26 min 40 sec", v.to_string());
+    
+    let n = parse_expr(r#"1 GiB in MB"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(1073.741824, unit::Unit::Megabyte)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"100 Mb in MB"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(12.5, unit::Unit::Megabyte)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(10 Gbps * 1 min) in GB"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(75.0, unit::Unit::Gigabyte)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_compound() {
+    let mut cxt = Context::new();
+    let compound = |v: f64, u: &str| { value::Value::Number(unit::Value::option(v, unit::Compound::from(u))) };
+    
+    let n = parse_expr(r#"2 kg * 3 kg"#).expect("Could not parse");
+    assert_eq!(Ok(compound(6.0, "kg²")), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"100 km / 2 h"#).expect("Could not parse");
+    assert_eq!(Node::new_div(Node::new_typecast(Node::new_number(100.0), Node::new_ident("km")), Node::new_typecast(Node::new_number(2.0), Node::new_ident("h"))), n);
+    let v = exec_node(n, &mut cxt).expect("Could not exec");
+    assert_eq!(compound(50.0, "km/h"), v);
+    assert_eq!("50 km/h", v.to_string());
+    
+    let n = parse_expr(r#"60 mi/h in km/h"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(60.0), Node::new_ident("mi/h")), Node::new_ident("km/h")), n);
+    assert_eq!(Ok(compound(96.56064, "km/h")), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"5 m^2 + 50 cm²"#).expect("Could not parse");
+    assert_eq!(Ok(compound(50050.0, "cm²")), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"9.81 m/s^2 * 2 s in km/h"#).expect("Could not parse");
+    assert_eq!(Ok(compound(70.632, "km/h")), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(3 kg) / (2 m*s)"#).expect("Could not parse");
+    assert_eq!(Ok(compound(1.5, "kg/m*s")), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"12 km / 3"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(4.0, unit::Unit::Kilometer)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_prefix() {
+    let mut cxt = Context::new();
+    let compound = |v: f64, u: &str| { value::Value::Number(unit::Value::option(v, unit::Compound::from(u))) };
+    
+    let n = parse_expr(r#"500 mg + 1 g"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(1.5, unit::Unit::Gram)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"250 µl in ml"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(0.25, unit::Unit::Milliliter)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2.4 GHz in MHz"#).expect("Could not parse");
+    assert_eq!(Ok(compound(2400.0, "MHz")), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"3 kW * 2 h in kW*h"#).expect("Could not parse");
+    assert_eq!(Ok(compound(6.0, "kW*h")), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_area() {
+    let mut cxt = Context::new();
+    
+    assert_eq!("l(((m(2) * m(3)) * m(0.5))) → 3000 l", &exec_line("2 m * 3 m * 0.5 m in l", &mut cxt));
+    assert_eq!("m²((ft(20) * ft(12))) → 22.2967296 m²", &exec_line("20 ft * 12 ft in m²", &mut cxt));
+    assert_eq!("ha(acres(5)) → 2.0234282112 ha", &exec_line("5 acres in ha", &mut cxt));
+    assert_eq!("gallons(ft³(10)) → 74.8051948052 gallon", &exec_line("10 ft³ in gallons", &mut cxt));
+    assert_eq!("(l(1000) / m²(2)) → 0.5 m", &exec_line("1000 l / 2 m²", &mut cxt));
+  }
+  
+  #[test]
+  fn parse_engineering() {
+    let mut cxt = Context::new_with_stdlib();
+    
+    assert_eq!("N((g * kg(80))) → 784.532 N", &exec_line("g * 80 kg in N", &mut cxt));
+    assert_eq!("km/h(mph(60)) → 96.56064 km/h", &exec_line("60 mph in km/h", &mut cxt));
+    assert_eq!("kWh((kW(2) * h(3))) → 6 kWh", &exec_line("2 kW * 3 h in kWh", &mut cxt));
+    assert_eq!("kcal(kJ(500)) → 119.502868069 kcal", &exec_line("500 kJ in kcal", &mut cxt));
+    assert_eq!("psi(bar(2.2)) → 31.9083023006 psi", &exec_line("2.2 bar in psi", &mut cxt));
+    assert_eq!("s((km(384400) / c)) → 1.282 sec", &exec_line("384400 km / c in s", &mut cxt));
+    assert_eq!("(g(9) * 2) → 18 g", &exec_line("9 g * 2", &mut cxt));
+    assert_eq!("(2 * g) → 19.6133 m/s²", &exec_line("2 * g", &mut cxt));
+  }
+  
+  #[test]
+  fn parse_currency() {
+    let mut cxt = Context::new_with_stdlib();
+    cxt.set_rates(currency::Rates::parse("date 2026-09-30\nbase USD\nEUR 0.8\nGBP 0.5").unwrap());
+    
+    assert_eq!("USD(100) → 100.00 USD", &exec_line("$100", &mut cxt));
+    assert_eq!("EUR(USD(100)) → 80.00 EUR (rates of 2026-09-30)", &exec_line("100 USD in EUR", &mut cxt));
+    assert_eq!("USD(GBP(12.5)) → 25.00 USD (rates of 2026-09-30)", &exec_line("£12.50 in $", &mut cxt));
+    assert_eq!("(USD(100) + EUR(50)) → 130.00 EUR (rates of 2026-09-30)", &exec_line("$100 + €50", &mut cxt));
+    assert_eq!("(EUR(50) * 3) → 150.00 EUR", &exec_line("50 € * 3", &mut cxt));
+    assert_eq!("(USD(1000) / EUR(100)) → 8 (rates of 2026-09-30)", &exec_line("$1,000 / €100", &mut cxt));
+    assert_eq!("(USD(100) > EUR(90)) → false (rates of 2026-09-30)", &exec_line("$100 > €90", &mut cxt));
+    assert_eq!("max(USD(100), EUR(90)) → 112.50 USD (rates of 2026-09-30)", &exec_line("max($100, €90)", &mut cxt));
+    assert_eq!("eur(usd(100)) → 80.00 EUR (rates of 2026-09-30)", &exec_line("100 usd in eur", &mut cxt));
+    assert_eq!("kg(USD(5)) → Incompatible units: USD and kg", &exec_line("$5 in kg", &mut cxt));
+    assert_eq!("(USD/hour(5) * hours(3)) → 15.00 USD", &exec_line("$5/hour * 3 hours", &mut cxt));
+    assert_eq!("USD/h(10) → 10 USD/h", &exec_line("10 $/h", &mut cxt));
+    assert_eq!("USD/h(EUR/h(40)) → 50 USD/h (rates of 2026-09-30)", &exec_line("40 €/h in $/h", &mut cxt));
+    assert_eq!("USD/d(USD/h(5)) → 120 USD/d", &exec_line("$5/h in $/d", &mut cxt));
+    assert_eq!("(USD(10) / 2) → 5.00 USD", &exec_line("$10/2", &mut cxt));
+    assert_eq!("kWh/USD(2) → 2 kWh/USD", &exec_line("2 kWh/$", &mut cxt));
+  }
+  
+  #[test]
+  fn parse_ingredient() {
+    let mut cxt = Context::new();
+    let quantity = |v: f64, u: unit::Unit, i: ingredient::Ingredient| { value::Value::Number(unit::Value::new(v, u).with_ingredient(Some(i))) };
+    
+    let n = parse_expr(r#"1 cup flour in g"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_ingredient(Node::new_typecast(Node::new_number(1.0), Node::new_ident("cup")), Node::new_ident("flour")), Node::new_ident("g")), n);
+    assert_eq!(Ok(quantity(120.0, unit::Unit::Gram, ingredient::Ingredient::Flour)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"198 g sugar in cups"#).expect("Could not parse");
+    assert_eq!(Ok(quantity(1.0, unit::Unit::Cup, ingredient::Ingredient::Sugar)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 cups flour + 4 tbsp flour in g"#).expect("Could not parse");
+    assert_eq!(Ok(quantity(270.0, unit::Unit::Gram, ingredient::Ingredient::Flour)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 cup flour + 1 cup sugar in g"#).expect("Could not parse");
+    assert_eq!(Ok(value::Value::new(318.0, unit::Unit::Gram)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 lb butter in cups"#).expect("Could not parse");
+    assert_eq!(Ok(quantity(1.99820427313, unit::Unit::Cup, ingredient::Ingredient::Butter)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"1 cup in g"#).expect("Could not parse");
+    assert_eq!(Err(error::Error::UnitMismatch("cup".to_string(), "g".to_string())), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_mismatch() {
+    let mut cxt = Context::new();
+    let mismatch = |l: &str, r: &str| { Err(error::Error::UnitMismatch(l.to_string(), r.to_string())) };
+    
+    let n = parse_expr(r#"3 kg + 2 cups"#).expect("Could not parse");
+    assert_eq!(mismatch("kg", "cup"), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"(3 kg + 2 cups) * 2"#).expect("Could not parse");
+    assert_eq!(mismatch("kg", "cup"), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"5 km in hours"#).expect("Could not parse");
+    assert_eq!(mismatch("km", "h"), exec_node(n, &mut cxt));
+    
+    assert_eq!("(kg(3) + cups(2)) → Incompatible units: kg and cup; (1 + 2) → 3", &exec_line("3 kg + 2 cups, 1 + 2", &mut cxt));
+    assert_eq!("(kg(3) > cups(2)) → Incompatible units: kg and cup", &exec_line("3 kg > 2 cups", &mut cxt));
+  }
+  
+  #[test]
+  fn parse_packed() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"63 in as packed"#).expect("Could not parse");
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(63.0), Node::new_ident("in")), Node::new_ident("packed")), n);
+    assert_eq!(Some(value::Format::Packed), n.format());
+    assert_eq!(Ok(value::Value::new(63.0, unit::Unit::Inch)), exec_node(n, &mut cxt));
+    
+    assert_eq!("packed(in(63)) → 5 ft 3 in", &exec_line("63 in as packed", &mut cxt));
+    assert_eq!("(x = packed(tbsp(18))) → 1 cup 2 tbsp", &exec_line("x = 18 tbsp as packed", &mut cxt));
+    assert_eq!("(x * 4) → 72 tbsp", &exec_line("x * 4", &mut cxt));
+    
+    let opts = rdl::Options{verbose: false, debug: false, packed: true};
+    let (_, res) = rdl::render_with_options(&mut cxt, "1 cup + 8 tbsp; 63 in as raw", 0, 0, None, Some(&opts));
+    assert_eq!("1 1/2 cups; 63 in", res.text());
+    
+    // a unit the expression is converted into is kept, and never packed up
+    let packed = |text: &str, cxt: &mut Context| {
+      let (_, res) = rdl::render_with_options(cxt, text, 0, 0, None, Some(&opts));
+      res.text().to_owned()
+    };
+    assert_eq!("236.5882365 ml", packed("1 cup in ml", &mut cxt));
+    assert_eq!("52 hours", packed("2 days 4 hours in hours", &mut cxt));
+    assert_eq!("2 hours 15 min", packed("(3 tasks * 45 min) in hours", &mut cxt));
+    assert_eq!("0.5 hours", packed("30 min in hours", &mut cxt));
+    assert_eq!("5 ft 3 in", packed("63 in in ft", &mut cxt));
+    assert_eq!("48 tbsp", packed("3 cups in tbsp", &mut cxt));
+    assert_eq!("2 days 4 hours", packed("52 hours", &mut cxt));
+    assert_eq!("2.5 l", packed("2500 ml", &mut cxt));
+  }
+  
+  #[test]
+  fn parse_declare() {
+    let mut cxt = Context::new();
+    
+    let n = parse_expr(r#"unit sprint = 2 weeks"#).expect("Could not parse");
+    assert_eq!(Node::new_declare("sprint", Node::new_typecast(Node::new_number(2.0), Node::new_ident("weeks"))), n);
+    assert_eq!(Err(error::Error::NoValue), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"unit crate = 24 bottles"#).expect("Could not parse");
+    assert_eq!(Node::new_declare("crate", Node::new_typecast(Node::new_number(24.0), Node::new_ident("bottles"))), n);
+    
+    assert_eq!("", &exec_line("unit sprint = 2 weeks", &mut cxt));
+    assert_eq!("days(sprints(10)) → 140 days", &exec_line("10 sprints in days", &mut cxt));
+    assert_eq!("sprint(days(21)) → 1.5 sprint", &exec_line("21 days in sprint", &mut cxt));
+    
+    assert_eq!("", &exec_line("unit crate = 24 bottles", &mut cxt));
+    assert_eq!("bottles(crates(3)) → 72 bottles", &exec_line("3 crates in bottles", &mut cxt));
+    assert_eq!("crate((bottle(36) + bottle(12))) → 2 crate", &exec_line("36 bottle + 12 bottle in crate", &mut cxt));
+    assert_eq!("crates(cases(4)) → 1 crate", &exec_line("unit case = 6 bottles; 4 cases in crates", &mut cxt));
+    assert_eq!("5 → 5", &exec_line("unit price 5", &mut cxt)); // just prose
+    
+    // built-in units cannot be declared again
+    assert_eq!("", &exec_line("unit m = 5 kg", &mut cxt));
+    assert_eq!("m(3) → 3 m", &exec_line("3 m", &mut cxt));
+    assert_eq!("ms(3) → 3 ms", &exec_line("3 ms", &mut cxt));
   }
   
   #[test]
   fn parse_unit_suffix() {
     let mut cxt = Context::new();
-    cxt.set("kg", unit::Value::raw(4.0));
+    cxt.set("kg", value::Value::raw(4.0));
     
     let n = parse_expr(r#"kg"#).expect("Could not parse");
     assert_eq!(Node::new_ident("kg"), n);
-    assert_eq!(Ok(unit::Value::raw(4.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(4.0)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"100 kg"#).expect("Could not parse");
     assert_eq!(Node::new_typecast(Node::new_number(100.0), Node::new_ident("kg")), n);
-    assert_eq!(Ok(unit::Value::new(100.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::new(100.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"(kg) kg"#).expect("Could not parse");
     assert_eq!(Node::new_typecast(Node::new_ident("kg"), Node::new_ident("kg")), n);
-    assert_eq!(Ok(unit::Value::new(4.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::new(4.0, unit::Unit::Kilogram)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"1 ok"#).expect("Could not parse");
     assert_eq!(Node::new_number(1.0), n);
-    assert_eq!(Ok(unit::Value::raw(1.0)), exec_node(n, &mut cxt));
+    assert_eq!(Ok(value::Value::raw(1.0)), exec_node(n, &mut cxt));
+    
+    let n = parse_expr(r#"2 * kg"#).expect("Could not parse");
+    assert_eq!(Node::new_mul(Node::new_number(2.0), Node::new_ident("kg")), n);
+    assert_eq!(Ok(value::Value::raw(8.0)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_unit_names() {
+    let mut cxt = Context::new_with_stdlib();
+    
+    assert_eq!("g(kilograms(2)) → 2000 g", &exec_line("2 kilograms in g", &mut cxt));
+    assert_eq!("ml(Litres(1.5)) → 1500 ml", &exec_line("1.5 Litres in ml", &mut cxt));
+    assert_eq!("tsp(tablespoons(2)) → 6 tsp", &exec_line("2 tablespoons in tsp", &mut cxt));
+    assert_eq!("kW(kilowatts(3)) → 3 kW", &exec_line("3 kilowatts in kW", &mut cxt));
+    assert_eq!("kg(t(2)) → 2000 kg", &exec_line("2 t in kg", &mut cxt));
+    assert_eq!("min(m(5)) → Incompatible units: m and min", &exec_line("5 m in min", &mut cxt));
+    assert_eq!("min(h((h(1) + min(30)))) → 90 min", &exec_line("1h 30m in min", &mut cxt));
+    assert_eq!("1 → 1", &exec_line("1st place", &mut cxt));
+    assert_eq!("st(2) → 2 st", &exec_line("2 st", &mut cxt));
+    
+    // a variable named like a unit is still the unit after a number
+    assert_eq!("(t = s(3)) → 3 sec", &exec_line("t = 3 s", &mut cxt));
+    assert_eq!("(t * 2) → 6 sec", &exec_line("t * 2", &mut cxt));
+    assert_eq!("t(2) → 2 t", &exec_line("2 t", &mut cxt));
+    
+    // a variable or a unit in the wrong case is not a label
+    assert_eq!("(x = 2) → 2", &exec_line("x = 2", &mut cxt));
+    assert_eq!("x(3) → Unknown unit: x", &exec_line("3 x", &mut cxt));
+    assert_eq!("gb(3) → Unknown unit: gb", &exec_line("3 gb", &mut cxt)); // a gigabit or a gigabyte
+    assert_eq!("(KM(5) + km(3)) → 8 km", &exec_line("5 KM + 3 km", &mut cxt));
   }
   
   #[test]
   fn parse_typecast() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let n = parse_expr(r#"100 kg in g"#).expect("Could not parse");
-    assert_eq!(Node::new_typecast(Node::new_typecast(Node::new_number(100.0), Node::new_ident("kg")), Node::new_ident("g")), n);
-    assert_eq!(Ok(unit::Value::new(100000.0, unit::Unit::Gram)), exec_node(n, &mut cxt));
+    assert_eq!(Node::new_convert(Node::new_typecast(Node::new_number(100.0), Node::new_ident("kg")), Node::new_ident("g")), n);
+    assert_eq!(Ok(value::Value::new(100000.0, unit::Unit::Gram)), exec_node(n, &mut cxt));
     
     let n = parse_expr(r#"100 + 200 kg in g"#).expect("Could not parse");
-    assert_eq!(Node::new_typecast(Node::new_add(Node::new_number(100.0), Node::new_typecast(Node::new_number(200.0), Node::new_ident("kg"))), Node::new_ident("g")), n);
-    assert_eq!(Ok(unit::Value::new(300000.0, unit::Unit::Gram)), exec_node(n, &mut cxt));
+    assert_eq!(Node::new_convert(Node::new_add(Node::new_number(100.0), Node::new_typecast(Node::new_number(200.0), Node::new_ident("kg"))), Node::new_ident("g")), n);
+    assert_eq!(Ok(value::Value::new(300000.0, unit::Unit::Gram)), exec_node(n, &mut cxt));
+  }
+  
+  #[test]
+  fn parse_line_refs() {
+    let mut cxt = Context::new();
+    assert_eq!("", &exec_line("# Groceries", &mut cxt));
+    assert_eq!("10 → 10", &exec_line("Apples 10", &mut cxt));
+    assert_eq!("(2 * 4) → 8", &exec_line("Pears 2 * 4", &mut cxt));
+    assert_eq!("sum → 18", &exec_line("Subtotal: sum", &mut cxt));
+    assert_eq!("(prev + 15%) → 20.7", &exec_line("With tax prev + 15%", &mut cxt));
+    assert_eq!("", &exec_line("", &mut cxt));
+    assert_eq!("(line2 * 3) → 30", &exec_line("Three times line2 * 3", &mut cxt));
+    assert_eq!("ans → 30", &exec_line("ans", &mut cxt));
+    assert_eq!("6 → 6", &exec_line("6", &mut cxt));
+    assert_eq!("average → 6", &exec_line("average", &mut cxt)); // lines that refer to others are left out
+    assert_eq!("", &exec_line("## Other", &mut cxt));
+    assert_eq!("total → 0", &exec_line("total", &mut cxt)); // nothing above in this block
+    
+    let mut cxt = Context::new();
+    assert_eq!("", &exec_line("# Week 1", &mut cxt));
+    assert_eq!("10 → 10", &exec_line("10", &mut cxt));
+    assert_eq!("20 → 20", &exec_line("20", &mut cxt));
+    assert_eq!("sum → 30", &exec_line("sum", &mut cxt));
+    assert_eq!("5 → 5", &exec_line("5", &mut cxt));
+    assert_eq!("total → 35", &exec_line("total", &mut cxt));
+    assert_eq!("line2 → 10", &exec_line("line2", &mut cxt)); // the heading is still a line
   }
   
   #[test]
   fn parse_in_context() {
     let mut cxt = Context::new();
-    cxt.set("a", unit::Value::raw(1.0));
-    cxt.set("b", unit::Value::raw(2.0));
-    cxt.set("c", unit::Value::raw(3.0));
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    cxt.set("c", value::Value::raw(3.0));
     
     let t = r#"100+200; 0"#;
     assert_eq!("(100 + 200) → 300; 0 → 0", &exec_line(t, &mut cxt));
     
     let t = r#"100 + (b * 100), but 0 is 0"#;
     assert_eq!("(100 + (b * 100)) → 300; 0 → 0; 0 → 0", &exec_line(t, &mut cxt));
+    
+    let mut cxt = Context::new_with_stdlib();
+    cxt.set("a", value::Value::raw(1.0));
+    cxt.set("b", value::Value::raw(2.0));
+    
+    let t = r#"The larger of a and b, max(a, b), then min(a, b)"#;
+    assert_eq!("a → 1; b → 2; max(a, b) → 2; min(a, b) → 1", &exec_line(t, &mut cxt));
+    
+    let t = r#"half(x) = x / 2 so half(b) is"#;
+    assert_eq!("half(b) → 1", &exec_line(t, &mut cxt));
+    
+    let t = r#"Budget 1,250,000 + 2.5e3, or 0xff, max(1,25)"#;
+    assert_eq!("(1250000 + 2500) → 1252500; 255 → 255; max(1, 25) → 25", &exec_line(t, &mut cxt));
+    assert_eq!("max(1, 250) → 250", &exec_line("max(1,250)", &mut cxt));
+    
+    let t = r#"Tip is 15% of 80, or 80 + 15%, and 12 is 15% of what"#;
+    assert_eq!("(15% of 80) → 12; (80 + 15%) → 92; (12 is 15% of what) → 80", &exec_line(t, &mut cxt));
+    
+    let t = r#"note = "a + b = " + (a + b) and that's it"#;
+    assert_eq!("(note = (\"a + b = \" + (a + b))) → a + b = 3", &exec_line(t, &mut cxt));
+    
+    let t = r#"If a < b then we ship; if a < b then 10 else 20, and b > 1, or not"#;
+    assert_eq!("(a < b) → true; (if (a < b) then 10 else 20) → 10; (b > 1) → true", &exec_line(t, &mut cxt));
   }
   
 }
@@ -5,6 +5,7 @@ use std::ops;
 use crossterm::style::Stylize;
 
 use crate::rdl::error;
+use crate::rdl::currency;
 
 const ZERO: char = '\0';
 
@@ -22,7 +23,12 @@ pub const SUB: char     = '-';
 pub const DIV: char     = '/';
 pub const MUL: char     = '*';
 pub const MOD: char     = '%';
+pub const POW: char     = '^';
+pub const LT: char      = '<';
+pub const GT: char      = '>';
+pub const BANG: char    = '!';
 pub const AT: char      = '@';
+pub const DEGREE: char  = '°';
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum TType {
@@ -36,7 +42,9 @@ pub enum TType {
   Typecast,
   LParen,
   RParen,
+  Comma,
   Symbol,
+  Currency, // a currency symbol written before an amount, as in '$100'
   End,
 }
 
@@ -66,24 +74,42 @@ impl Token {
       TType::String => Some(format!("{}", ttext.cyan())),
       TType::Operator => Some(format!("{}", ttext.green())),
       TType::Symbol => Some(format!("{}", ttext.blue())),
+      TType::Currency => Some(format!("{}", ttext.bold())),
       _ => None,
     }
   }
 }
 
+impl Token {
+  /// The numeric value of a number token. Digit separators are ignored
+  /// and integers may be written in hexadecimal, octal or binary.
+  pub fn number(&self) -> Result<f64, error::Error> {
+    let text: String = self.ttext.chars().filter(|c| { *c != '_' && *c != COMMA }).collect();
+    let radix = match text.get(0..2) {
+      Some("0x") | Some("0X") => 16,
+      Some("0o") | Some("0O") => 8,
+      Some("0b") | Some("0B") => 2,
+      _ => return Ok(text.parse::<f64>()?),
+    };
+    Ok(u64::from_str_radix(&text[2..], radix)? as f64)
+  }
+}
+
 impl fmt::Display for Token {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     write!(f, "<{}>", self.ttext)
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Scanner<'a> {
   text: &'a str,
   data: str::Chars<'a>,
   tokens: Vec<Token>,
-  peek: [char; 2],
+  peek: [char; 5],
   index: usize, // index in text, in bytes
+  last: TType, // the last token scanned that is not whitespace
+  calls: Vec<bool>, // for each open parenthesis, whether it opens a call
 }
 
 impl<'a> fmt::Display for Scanner<'a> {
@@ -98,8 +124,10 @@ impl<'a> Scanner<'a> {
       text: text,
       data: text.chars(),
       tokens: Vec::new(),
-      peek: [ZERO, ZERO],
+      peek: [ZERO; 5],
       index: 0,
+      last: TType::End,
+      calls: Vec::new(),
     }
   }
   
@@ -213,8 +241,8 @@ impl<'a> Scanner<'a> {
     }
   }
   
-  /// Look ahead for the next token type in the stream. Nothign is consumed.
-  fn la_token(&mut self) -> Option<&Token> {
+  /// Look ahead for the next token in the stream. Nothign is consumed.
+  pub fn la_token(&mut self) -> Option<&Token> {
     if self.tokens.len() == 0 {
       let _ = self.scan(); // ignore error, just produce none
     }
@@ -300,6 +328,14 @@ impl<'a> Scanner<'a> {
   }
   
   fn push(&mut self, tok: Token) {
+    match tok.ttype {
+      TType::LParen => self.calls.push(self.last == TType::Ident),
+      TType::RParen => { self.calls.pop(); },
+      _ => {},
+    };
+    if tok.ttype != TType::Whitespace {
+      self.last = tok.ttype;
+    }
     self.tokens.push(tok);
   }
   
@@ -320,12 +356,16 @@ impl<'a> Scanner<'a> {
         return self.scan_word();
       }else if Self::is_number_start(c) {
         return self.scan_number();
-      }else if Self::is_operator(c) {
+      }else if Self::is_operator(c) || self.is_not_equal() {
         return self.scan_operator();
       }else if Self::is_whitespace(c) {
         return self.scan_whitespace();
+      }else if c == QUOTE {
+        return self.scan_string();
       }else if Self::is_symbol(c) {
         return self.scan_symbol();
+      }else if Self::is_currency(c) {
+        return self.scan_currency();
       }
     }
     Err(error::Error::TokenNotMatched)
@@ -340,9 +380,9 @@ impl<'a> Scanner<'a> {
           break;
         }else if Self::is_number_start(c) {
           break;
-        }else if Self::is_operator(c) {
+        }else if Self::is_operator(c) || self.is_not_equal() {
           break;
-        }else if Self::is_symbol(c) {
+        }else if Self::is_symbol(c) || Self::is_currency(c) || c == QUOTE {
           break;
         }else if c == ESCAPE {
           buf.push_str(&self.escape()?)
@@ -404,14 +444,17 @@ impl<'a> Scanner<'a> {
   
   fn scan_operator(&mut self) -> Result<(), error::Error> {
     let idx = self.index;
+    // operators are a single character each, except for '**' which is an
+    // alias for '^' and the comparisons '<=', '>=' and '!='; adjacent
+    // operators, as in '3 * -2', produce separate tokens
     let mut buf = String::new();
-    while let Some(c) = self.peek() {
-      if Self::is_operator(c) {
-        buf.push(c);
-      }else{
-        break;
-      }
-      self.skip(); // consume the character
+    buf.push(self.assert_fn(|c| { Self::is_operator(c) || c == BANG })?);
+    if buf.starts_with(MUL) && self.expect(MUL) {
+      buf.push(MUL);
+    }else if (buf.starts_with(LT) || buf.starts_with(GT)) && self.expect(EQUAL) {
+      buf.push(EQUAL);
+    }else if buf.starts_with(BANG) {
+      buf.push(self.assert(EQUAL)?);
     }
     self.push(Token{
       ttype: TType::Operator,
@@ -421,6 +464,24 @@ impl<'a> Scanner<'a> {
     Ok(())
   }
   
+  fn scan_string(&mut self) -> Result<(), error::Error> {
+    let idx = self.index;
+    let tok = match self.string() {
+      Ok(text) => Token{
+        ttype: TType::String,
+        ttext: text,
+        range: idx..self.index,
+      },
+      Err(_) => Token{ // unterminated, so this is just text
+        ttype: TType::Verbatim,
+        ttext: self.text[idx..self.index].to_string(),
+        range: idx..self.index,
+      },
+    };
+    self.push(tok);
+    Ok(())
+  }
+  
   fn scan_whitespace(&mut self) -> Result<(), error::Error> {
     let idx = self.index;
     let ws = self.whitespace()?;
@@ -435,9 +496,18 @@ impl<'a> Scanner<'a> {
   fn scan_symbol(&mut self) -> Result<(), error::Error> {
     let idx = self.index;
     if let Some(c) = self.next() {
+      if c == EQUAL && self.expect(EQUAL) {
+        self.push(Token{
+          ttype: TType::Operator,
+          ttext: "==".to_string(),
+          range: idx..self.index,
+        });
+        return Ok(());
+      }
       let ttype = match c {
         LPAREN => TType::LParen,
         RPAREN => TType::RParen,
+        COMMA  => TType::Comma,
         EQUAL  => TType::Assign,
         _      => TType::Symbol,
       };
@@ -450,6 +520,17 @@ impl<'a> Scanner<'a> {
     Ok(())
   }
   
+  fn scan_currency(&mut self) -> Result<(), error::Error> {
+    let idx = self.index;
+    let c = self.assert_fn(|c| { Self::is_currency(c) })?;
+    self.push(Token{
+      ttype: TType::Currency,
+      ttext: c.to_string(),
+      range: idx..self.index,
+    });
+    Ok(())
+  }
+  
   fn skip_ws(&mut self) -> Result<(), error::Error> {
     let _ = self.whitespace()?;
     Ok(())
@@ -469,11 +550,11 @@ impl<'a> Scanner<'a> {
   }
   
   fn is_ident(c: char) -> bool {
-    c.is_alphabetic() || c.is_digit(10) || c == '_'
+    c.is_alphabetic() || c.is_digit(10) || c == '_' || c == '²' || c == '³' // as in 'm²'
   }
   
   fn is_ident_start(c: char) -> bool {
-    c.is_alphabetic() || c == '_'
+    c.is_alphabetic() || c == '_' || c == DEGREE // as in '°C'
   }
   
   fn is_number_start(c: char) -> bool {
@@ -485,11 +566,20 @@ impl<'a> Scanner<'a> {
   }
   
   fn is_operator(c: char) -> bool {
-    c == ADD || c == SUB || c == MUL || c == DIV || c == MOD
+    c == ADD || c == SUB || c == MUL || c == DIV || c == MOD || c == POW || c == LT || c == GT
+  }
+  
+  /// A lone '!' is not an operator, only the '!=' comparison is.
+  fn is_not_equal(&mut self) -> bool {
+    self.peek() == Some(BANG) && self.peek_n(1) == Some(EQUAL)
   }
   
   fn is_symbol(c: char) -> bool {
-    c == EQUAL || c == LPAREN || c == RPAREN
+    c == EQUAL || c == LPAREN || c == RPAREN || c == COMMA
+  }
+  
+  fn is_currency(c: char) -> bool {
+    currency::symbol(c).is_some()
   }
   
   fn ident(&mut self) -> Result<String, error::Error> {
@@ -507,10 +597,16 @@ impl<'a> Scanner<'a> {
   }
   
   fn integer(&mut self) -> Result<String, error::Error> {
+    self.digits(10)
+  }
+  
+  /// Scan a run of digits in the provided radix. Digits may be separated
+  /// by single underscores, as in '1_000', which are kept in the result.
+  fn digits(&mut self, radix: u32) -> Result<String, error::Error> {
     let mut buf = String::new();
-    buf.push(self.assert_fn(|c| { c.is_digit(10) })?);
+    buf.push(self.assert_fn(|c| { c.is_digit(radix) })?);
     while let Some(c) = self.peek() {
-      if c.is_digit(10) {
+      if c.is_digit(radix) || (c == '_' && self.peek_n(1).is_some_and(|c| { c.is_digit(radix) })) {
         buf.push(c);
       }else{
         break;
@@ -522,7 +618,23 @@ impl<'a> Scanner<'a> {
   
   fn number(&mut self) -> Result<String, error::Error> {
     let mut buf = String::new();
+    if let Some(radix) = self.radix() {
+      buf.push(self.assert('0')?);
+      buf.push(self.next().unwrap_or(ZERO));
+      buf.push_str(&self.digits(radix)?);
+      return Ok(buf);
+    }
     buf.push_str(&self.integer()?);
+    // a comma only groups thousands if the leading group has at most three
+    // digits and every comma is followed by exactly three; otherwise it is a
+    // separator, as in 'max(1,25)'. Within the arguments of a call a comma
+    // always separates, as in 'max(1,250)'.
+    if buf.len() <= 3 && !buf.starts_with('0') && !buf.contains('_') && !self.in_call() {
+      while self.is_group() {
+        buf.push(self.assert(COMMA)?);
+        buf.push_str(&self.integer()?);
+      }
+    }
     if let Some(c1) = self.peek_n(0) {
       if c1 == '.' {
         if let Some(c2) = self.peek_n(1) {
@@ -534,9 +646,70 @@ impl<'a> Scanner<'a> {
         }
       }
     }
+    if self.is_exponent() {
+      buf.push(self.next().unwrap_or(ZERO));
+      if let Some(sign) = self.expect_fn(|c| { c == ADD || c == SUB }) {
+        buf.push(sign);
+      }
+      buf.push_str(&self.integer()?);
+    }
     Ok(buf)
   }
   
+  /// If the stream begins with a radix prefix, '0x', '0b' or '0o', that is
+  /// followed by a digit in that radix, produce the radix. Nothing is
+  /// consumed.
+  fn radix(&mut self) -> Option<u32> {
+    if self.peek() != Some('0') {
+      return None;
+    }
+    let radix = match self.peek_n(1) {
+      Some('x') | Some('X') => 16,
+      Some('o') | Some('O') => 8,
+      Some('b') | Some('B') => 2,
+      _ => return None,
+    };
+    if self.peek_n(2).is_some_and(|c| { c.is_digit(radix) }) {
+      Some(radix)
+    }else{
+      None
+    }
+  }
+  
+  /// Determine if the innermost open parenthesis opens the arguments of a
+  /// call, as in 'max(1,250)'.
+  fn in_call(&self) -> bool {
+    self.calls.last().copied().unwrap_or(false)
+  }
+  
+  /// Determine if the stream begins with a thousands group: a comma
+  /// followed by exactly three digits. Nothing is consumed.
+  fn is_group(&mut self) -> bool {
+    if self.peek() != Some(COMMA) {
+      return false;
+    }
+    for i in 1..=3 {
+      if !self.peek_n(i).is_some_and(|c| { c.is_digit(10) }) {
+        return false;
+      }
+    }
+    !self.peek_n(4).is_some_and(|c| { c.is_digit(10) || c == '_' })
+  }
+  
+  /// Determine if the stream begins with an exponent, like 'e6' or 'E-3'.
+  /// Nothing is consumed.
+  fn is_exponent(&mut self) -> bool {
+    match self.peek() {
+      Some('e') | Some('E') => {},
+      _ => return false,
+    };
+    let n = match self.peek_n(1) {
+      Some(ADD) | Some(SUB) => 2,
+      _ => 1,
+    };
+    self.peek_n(n).is_some_and(|c| { c.is_digit(10) })
+  }
+  
   fn string(&mut self) -> Result<String, error::Error> {
     let mut buf = String::new();
     self.assert(QUOTE)?;
@@ -608,7 +781,9 @@ mod tests {
     let mut t = Scanner::new(&s);
     assert_eq!(Some('F'), t.peek());
     assert_eq!(Some('o'), t.peek_n(1));
-    assert_eq!(None,      t.peek_n(2)); // LA is too small
+    assert_eq!(Some(' '), t.peek_n(3));
+    assert_eq!(Some('b'), t.peek_n(4));
+    assert_eq!(None,      t.peek_n(5)); // LA is too small
     assert_eq!(Some('F'), t.peek_n(0));
     assert_eq!(Some('o'), t.peek_n(1));
     assert_eq!(Some('F'), t.next());
@@ -663,6 +838,74 @@ mod tests {
     assert_eq!(Ok(Token::new(TType::Number, "100", 0..3)), t.token());
     assert_eq!(Ok(Token::new(TType::Verbatim, ".", 3..4)), t.token());
     
+    let s = r#"1.5e6 2E-3 4e+2 3em"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Number, "1.5e6", 0..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 5..6)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "2E-3", 6..10)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 10..11)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "4e+2", 11..15)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 15..16)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "3", 16..17)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "em", 17..19)), t.token());
+    
+    let s = r#"0xff 0b1010 0o17 0xg"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Number, "0xff", 0..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 4..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "0b1010", 5..11)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 11..12)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "0o17", 12..16)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 16..17)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "0", 17..18)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "xg", 18..20)), t.token());
+    
+    let s = r#"1_000_000 1__0 2_"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Number, "1_000_000", 0..9)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 9..10)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1", 10..11)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "__0", 11..14)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 14..15)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "2", 15..16)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "_", 16..17)), t.token());
+    
+    let s = r#"1,000,000.5 1,25 1234,567 1,2345"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Number, "1,000,000.5", 0..11)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 11..12)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1", 12..13)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 13..14)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "25", 14..16)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 16..17)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1234", 17..21)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 21..22)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "567", 22..25)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 25..26)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1", 26..27)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 27..28)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "2345", 28..32)), t.token());
+    
+    let s = r#"max(1,250) (1,250)"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Ident, "max", 0..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::LParen, "(", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1", 4..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 5..6)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "250", 6..9)), t.token());
+    assert_eq!(Ok(Token::new(TType::RParen, ")", 9..10)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 10..11)), t.token());
+    assert_eq!(Ok(Token::new(TType::LParen, "(", 11..12)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1,250", 12..17)), t.token());
+    
+    assert_eq!(Ok(1500000.0), Token::new(TType::Number, "1.5e6", 0..5).number());
+    assert_eq!(Ok(0.002), Token::new(TType::Number, "2E-3", 0..4).number());
+    assert_eq!(Ok(255.0), Token::new(TType::Number, "0xff", 0..4).number());
+    assert_eq!(Ok(10.0), Token::new(TType::Number, "0b1010", 0..6).number());
+    assert_eq!(Ok(15.0), Token::new(TType::Number, "0o17", 0..4).number());
+    assert_eq!(Ok(1000000.0), Token::new(TType::Number, "1_000_000", 0..9).number());
+    assert_eq!(Ok(1000000.5), Token::new(TType::Number, "1,000,000.5", 0..11).number());
+    
     let s = r#"Hello 122"#;
     let mut t = Scanner::new(s);
     assert_eq!(Ok(Token::new(TType::Ident, "Hello", 0..5)), t.token());
@@ -688,9 +931,61 @@ mod tests {
     assert_eq!(Ok(Token::new(TType::Number, "122", 6..9)), t.token());
     assert_eq!(Ok(Token::new(TType::Verbatim, ".", 9..10)), t.token());
     
-    let s = r#"+-*/%"#; // consuming operators is greedy
+    let s = r#"+-*/%"#; // each operator is its own token
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Operator, "+", 0..1)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "-", 1..2)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "*", 2..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "/", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "%", 4..5)), t.token());
+    
+    let s = r#"2**3^4"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Number, "2", 0..1)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "**", 1..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "3", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "^", 4..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "4", 5..6)), t.token());
+    
+    let s = r#"3*-2"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Number, "3", 0..1)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "*", 1..2)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "-", 2..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "2", 3..4)), t.token());
+    
+    let s = r#"<<=>>===!=="#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Operator, "<", 0..1)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "<=", 1..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, ">", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, ">=", 4..6)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "==", 6..8)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "!=", 8..10)), t.token());
+    assert_eq!(Ok(Token::new(TType::Assign, "=", 10..11)), t.token());
+    
+    let s = r#"Hi! a!=b"#; // a lone '!' is just text
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Ident, "Hi", 0..2)), t.token());
+    assert_eq!(Ok(Token::new(TType::Verbatim, "! ", 2..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "a", 4..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "!=", 5..7)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "b", 7..8)), t.token());
+    
+    let s = r#"x = "Total: \"a\"\n" "#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Ident, "x", 0..1)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 1..2)), t.token());
+    assert_eq!(Ok(Token::new(TType::Assign, "=", 2..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::String, "Total: \"a\"\n", 4..20)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 20..21)), t.token());
+    
+    let s = r#"Say "hi"#; // unterminated, so not a string
     let mut t = Scanner::new(s);
-    assert_eq!(Ok(Token::new(TType::Operator, "+-*/%", 0..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "Say", 0..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Verbatim, "\"hi", 4..7)), t.token());
     
     let s = r#"Hello    = 122"#;
     let mut t = Scanner::new(s);
@@ -713,9 +1008,11 @@ mod tests {
     let s = r#"Hello, there, Mr.=122"#;
     let mut t = Scanner::new(s);
     assert_eq!(Ok(Token::new(TType::Ident, "Hello", 0..5)), t.token());
-    assert_eq!(Ok(Token::new(TType::Verbatim, ", ", 5..7)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 5..6)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 6..7)), t.token());
     assert_eq!(Ok(Token::new(TType::Ident, "there", 7..12)), t.token());
-    assert_eq!(Ok(Token::new(TType::Verbatim, ", ", 12..14)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 12..13)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 13..14)), t.token());
     assert_eq!(Ok(Token::new(TType::Ident, "Mr", 14..16)), t.token());
     assert_eq!(Ok(Token::new(TType::Verbatim, ".", 16..17)), t.token());
     assert_eq!(Ok(Token::new(TType::Assign, "=", 17..18)), t.token());
@@ -735,6 +1032,16 @@ mod tests {
     assert_eq!(Ok(Token::new(TType::Ident, "b", 9..10)), t.token());
     assert_eq!(Ok(Token::new(TType::RParen, ")", 10..11)), t.token());
     
+    let s = r#"max(a, 1)"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Ident, "max", 0..3)), t.token());
+    assert_eq!(Ok(Token::new(TType::LParen, "(", 3..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Ident, "a", 4..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Comma, ",", 5..6)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 6..7)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "1", 7..8)), t.token());
+    assert_eq!(Ok(Token::new(TType::RParen, ")", 8..9)), t.token());
+    
     let s = r#"1 as kg"#;
     let mut t = Scanner::new(s);
     assert_eq!(Ok(Token::new(TType::Number, "1", 0..1)), t.token());
@@ -752,5 +1059,19 @@ mod tests {
     assert_eq!(Ok(Token::new(TType::Typecast, "in", 5..7)), t.token());
     assert_eq!(Ok(Token::new(TType::Whitespace, " ", 7..8)), t.token());
     assert_eq!(Ok(Token::new(TType::Ident, "g", 8..9)), t.token());
+    
+    let s = r#"$100 + €5 in $"#;
+    let mut t = Scanner::new(s);
+    assert_eq!(Ok(Token::new(TType::Currency, "$", 0..1)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "100", 1..4)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 4..5)), t.token());
+    assert_eq!(Ok(Token::new(TType::Operator, "+", 5..6)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 6..7)), t.token());
+    assert_eq!(Ok(Token::new(TType::Currency, "€", 7..10)), t.token());
+    assert_eq!(Ok(Token::new(TType::Number, "5", 10..11)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 11..12)), t.token());
+    assert_eq!(Ok(Token::new(TType::Typecast, "in", 12..14)), t.token());
+    assert_eq!(Ok(Token::new(TType::Whitespace, " ", 14..15)), t.token());
+    assert_eq!(Ok(Token::new(TType::Currency, "$", 15..16)), t.token());
   }
 }
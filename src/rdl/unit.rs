@@ -2,137 +2,568 @@ use std::fmt;
 use std::ops;
 
 use crate::util;
+use crate::rdl::error;
+use crate::rdl::ingredient::Ingredient;
 
-const CONVERSION: [[f64; 11]; 11] = [
- //                 Teaspoon,     Tablespoon,         Cup,                 Quart,               Gallon,              Liter,               Deciliter,           Centiliter,        Milliliter,        Gram,      Kilogram,
- /* Teaspoon */   [ 1.0,          1.0 / 3.0,          0.0208333333333333,  0.0052083333333333,  0.0013020833333333,  0.0049289249029002,  0.0492892490290018,  4.92892490290018,  4928.92490290018,  0.0,       0.0 ],
- /* Tablespoon */ [ 3.0,          1.0,                0.0625,              0.015625,            0.00390625,          0.0147867747087005,  0.147867747087005,   14.7867747087005,  14786.7747087005,  0.0,       0.0 ],
- /* Cup */        [ 48.0,         16.0,               1.0,                 0.25,                0.0625,              0.236588395339209,   1.47867747087005,    1478.67747087005,  14786774.7087005,  0.0,       0.0 ],
- /* Quart */      [ 192.0,        64.0,               4.0,                 1.0,                 0.25,                0.946353581356835,   9.46353581356834,    946.353581356834,  946353.581356834,  0.0,       0.0 ],
- /* Gallon */     [ 768.0,        256.0,              16.0,                4.0,                 1.0,                 3.78541432542734,    37.8541432542734,    3785.41432542734,  3785414.32542734,  0.0,       0.0 ],
- /* Liter */      [ 202.884,      67.628,             4.22675,             1.0566875,           0.264171875,         1.0,                 10.0,                100.0,             1000.0,            0.0,       0.0 ],
- /* Deciliter */  [ 20.2884,      6.7628,             0.67628,             0.10566875,          0.0264171875,        0.1,                 1.0,                 10.0,              100.0,             0.0,       0.0 ],
- /* Centiliter */ [ 0.202884,     0.067628,           0.00067628,          0.0010566875,        0.000264171875,      0.01,                0.1,                 1.0,               10.0,              0.0,       0.0 ],
- /* Milliliter */ [ 0.000202884,  0.000067628,        0.000000067628,      0.0000010566875,     0.000000264171875,   0.001,               0.01,                0.1,               1.0,               0.0,       0.0 ],
- /* Gram */       [ 0.0,          0.0,                0.0,                 0.0,                 0.0,                 0.0,                 0.0,                 0.0,               0.0,               1.0,       0.001 ],
- /* Kilogram */   [ 0.0,          0.0,                0.0,                 0.0,                 0.0,                 0.0,                 0.0,                 0.0,               0.0,               1000.0,    1.0 ],
-];
+/// The physical quantity a unit measures. Only units of the same
+/// dimension can be converted between.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Dimension {
+  Mass,   // base: kilogram
+  Volume, // base: cubic meter
+  Length, // base: meter
+  Area,   // base: square meter
+  Temperature, // base: kelvin
+  Time,   // base: second
+  Data,   // base: bit
+  Rate,   // base: bit per second
+  Power,  // base: watt
+  Frequency, // base: hertz
+  Speed,  // base: meter per second
+  Energy, // base: joule
+  Force,  // base: newton
+  Pressure, // base: pascal
+  Money,  // base: the base currency of the exchange rates
+  Custom(usize), // a unit declared in a document, as in 'unit bottle'
+}
+
+/// The family of units a unit belongs to. Values are only scaled up or
+/// down within the same system, so cups never become liters on their own.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum System {
+  Metric,
+  Customary, // US customary
+  Nautical,
+  Imperial, // British units with no US counterpart
+  Binary, // IEC, powers of 1024
+  Bits,   // data measured in bits rather than bytes
+  Other,  // units outside the others, like calories and atmospheres
+}
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Unit {
-  Teaspoon,    // base
-  Tablespoon,  // 3x tsp
-  Cup,         // 16x tbsp
-  Quart,       // 4x cup
-  Gallon,      // 4x quart
+  Teaspoon,
+  Tablespoon,
+  Cup,
+  Quart,
+  Gallon,
+  
+  Liter,
+  Deciliter,
+  Centiliter,
+  Milliliter,
+  
+  Gram,
+  Kilogram,
+  Tonne,
+  Ounce,
+  Pound,
+  Stone,
+  Ton,
+  
+  Millimeter,
+  Centimeter,
+  Meter,
+  Kilometer,
+  Inch,
+  Foot,
+  Yard,
+  Mile,
+  NauticalMile,
+  
+  Hectare,
+  Acre,
+  
+  Celsius,
+  Fahrenheit,
+  Kelvin,
+  
+  Second,
+  Minute,
+  Hour,
+  Day,
+  Week,
+  Month,
+  Year,
+  
+  Bit,
+  Kilobit,
+  Megabit,
+  Gigabit,
+  Terabit,
+  Byte,
+  Kilobyte,
+  Megabyte,
+  Gigabyte,
+  Terabyte,
+  Kibibyte,
+  Mebibyte,
+  Gibibyte,
+  Tebibyte,
+  
+  BitPerSecond,
+  KilobitPerSecond,
+  MegabitPerSecond,
+  GigabitPerSecond,
+  
+  Watt,
+  Hertz,
   
-  Liter,       // base
-  Deciliter,   // 1/10 base
-  Centiliter,  // 1/100 base
-  Milliliter,  // 1/1000 base
+  Mph,
+  Knot,
   
-  Gram,        // base
-  Kilogram,    // 1000x grams
+  Joule,
+  KilowattHour,
+  Calorie,
+  Kilocalorie,
+  
+  Horsepower,
+  
+  Newton,
+  PoundForce,
+  
+  Pascal,
+  Bar,
+  Psi,
+  Atmosphere,
 }
 
 impl Unit {
+  /// Every supported unit.
+  pub const ALL: [Unit; 70] = [
+    Unit::Teaspoon, Unit::Tablespoon, Unit::Cup, Unit::Quart, Unit::Gallon,
+    Unit::Liter, Unit::Deciliter, Unit::Centiliter, Unit::Milliliter,
+    Unit::Gram, Unit::Kilogram, Unit::Tonne, Unit::Ounce, Unit::Pound, Unit::Stone, Unit::Ton,
+    Unit::Millimeter, Unit::Centimeter, Unit::Meter, Unit::Kilometer,
+    Unit::Inch, Unit::Foot, Unit::Yard, Unit::Mile, Unit::NauticalMile,
+    Unit::Hectare, Unit::Acre,
+    Unit::Celsius, Unit::Fahrenheit, Unit::Kelvin,
+    Unit::Second, Unit::Minute, Unit::Hour, Unit::Day, Unit::Week, Unit::Month, Unit::Year,
+    Unit::Bit, Unit::Kilobit, Unit::Megabit, Unit::Gigabit, Unit::Terabit,
+    Unit::Byte, Unit::Kilobyte, Unit::Megabyte, Unit::Gigabyte, Unit::Terabyte,
+    Unit::Kibibyte, Unit::Mebibyte, Unit::Gibibyte, Unit::Tebibyte,
+    Unit::BitPerSecond, Unit::KilobitPerSecond, Unit::MegabitPerSecond, Unit::GigabitPerSecond,
+    Unit::Watt, Unit::Hertz,
+    Unit::Mph, Unit::Knot,
+    Unit::Joule, Unit::KilowattHour, Unit::Calorie, Unit::Kilocalorie,
+    Unit::Horsepower,
+    Unit::Newton, Unit::PoundForce,
+    Unit::Pascal, Unit::Bar, Unit::Psi, Unit::Atmosphere,
+  ];
+  
+  /// Look up a unit as it is written in a document. Symbols are matched
+  /// as written, since 'm' is a meter but 'M' is not, while names and
+  /// abbreviations are matched regardless of case, in the singular or the
+  /// plural and in British or American spelling, as in 'Litres'. Where a
+  /// symbol could mean more than one unit it means the SI one: 'm' is a
+  /// meter and 't' is a metric tonne, not a ton. Only the parts of a
+  /// compound duration, as in '1h 30m', are read otherwise, as minutes.
   pub fn from(name: &str) -> Option<Unit> {
-    match name.to_owned().trim().to_lowercase().as_str() {
-      "tsp" | "tsps"       => Some(Unit::Teaspoon),
-      "tbsp" | "tbsps"     => Some(Unit::Tablespoon),
-      "cup" | "cups"       => Some(Unit::Cup),
-      "quart" | "quarts"   => Some(Unit::Quart),
-      "gallon" | "gallons" => Some(Unit::Gallon),
+    let name = name.trim();
+    Unit::from_written(name).or_else(|| { Unit::from_name(&name.to_lowercase()) })
+  }
+  
+  /// Look up a unit by a symbol, or by a name written in lower case.
+  fn from_written(name: &str) -> Option<Unit> {
+    Unit::from_symbol(name).or_else(|| { Unit::from_name(name) })
+  }
+  
+  /// Look up a unit by its name or an abbreviation, in lower case.
+  fn from_name(name: &str) -> Option<Unit> {
+    let name = name.replace("metre", "meter").replace("litre", "liter").replace("gramme", "gram");
+    if let Some(unit) = Unit::from_abbreviation(&name).or_else(|| { Unit::from_word(&name) }) {
+      return Some(unit);
+    }
+    if name == "feet" {
+      return Some(Unit::Foot);
+    }
+    // only full names have a plural formed from them, so that 'has' is
+    // never a number of hectares
+    name.strip_suffix('s').and_then(Unit::from_word).or_else(|| {
+      name.strip_suffix("es").and_then(Unit::from_word)
+    })
+  }
+  
+  /// Look up a unit by its full name, in lower case and in the singular.
+  fn from_word(name: &str) -> Option<Unit> {
+    match name {
+      "teaspoon"    => Some(Unit::Teaspoon),
+      "tablespoon"  => Some(Unit::Tablespoon),
+      "cup"         => Some(Unit::Cup),
+      "quart"       => Some(Unit::Quart),
+      "gallon"      => Some(Unit::Gallon),
+      
+      "liter"       => Some(Unit::Liter),
+      "deciliter"   => Some(Unit::Deciliter),
+      "centiliter"  => Some(Unit::Centiliter),
+      "milliliter"  => Some(Unit::Milliliter),
+      
+      "gram"        => Some(Unit::Gram),
+      "kilogram" | "kilo" => Some(Unit::Kilogram),
+      "tonne"       => Some(Unit::Tonne),
+      "ounce"       => Some(Unit::Ounce),
+      "pound"       => Some(Unit::Pound),
+      "stone"       => Some(Unit::Stone),
+      "ton"         => Some(Unit::Ton),
       
-      "l"                  => Some(Unit::Liter),
-      "dl"                 => Some(Unit::Deciliter),
-      "cl"                 => Some(Unit::Centiliter),
-      "ml"                 => Some(Unit::Milliliter),
+      "millimeter"  => Some(Unit::Millimeter),
+      "centimeter"  => Some(Unit::Centimeter),
+      "meter"       => Some(Unit::Meter),
+      "kilometer"   => Some(Unit::Kilometer),
+      "inch"        => Some(Unit::Inch),
+      "foot"        => Some(Unit::Foot),
+      "yard"        => Some(Unit::Yard),
+      "mile"        => Some(Unit::Mile),
       
-      "g"                  => Some(Unit::Gram),
-      "kg"                 => Some(Unit::Kilogram),
+      "hectare"     => Some(Unit::Hectare),
+      "acre"        => Some(Unit::Acre),
       
-      _                    => None,
+      "celsius" | "centigrade" => Some(Unit::Celsius),
+      "fahrenheit"  => Some(Unit::Fahrenheit),
+      "kelvin"      => Some(Unit::Kelvin),
+      
+      "second"      => Some(Unit::Second),
+      "minute"      => Some(Unit::Minute),
+      "hour"        => Some(Unit::Hour),
+      "day"         => Some(Unit::Day),
+      "week"        => Some(Unit::Week),
+      "month"       => Some(Unit::Month),
+      "year"        => Some(Unit::Year),
+      
+      "bit"         => Some(Unit::Bit),
+      "kilobit"     => Some(Unit::Kilobit),
+      "megabit"     => Some(Unit::Megabit),
+      "gigabit"     => Some(Unit::Gigabit),
+      "terabit"     => Some(Unit::Terabit),
+      "byte"        => Some(Unit::Byte),
+      "kilobyte"    => Some(Unit::Kilobyte),
+      "megabyte"    => Some(Unit::Megabyte),
+      "gigabyte"    => Some(Unit::Gigabyte),
+      "terabyte"    => Some(Unit::Terabyte),
+      "kibibyte"    => Some(Unit::Kibibyte),
+      "mebibyte"    => Some(Unit::Mebibyte),
+      "gibibyte"    => Some(Unit::Gibibyte),
+      "tebibyte"    => Some(Unit::Tebibyte),
+      
+      "watt"        => Some(Unit::Watt),
+      "hertz"       => Some(Unit::Hertz),
+      "knot"        => Some(Unit::Knot),
+      "joule"       => Some(Unit::Joule),
+      "calorie"     => Some(Unit::Calorie),
+      "kilocalorie" => Some(Unit::Kilocalorie),
+      "horsepower"  => Some(Unit::Horsepower),
+      "newton"      => Some(Unit::Newton),
+      "pascal"      => Some(Unit::Pascal),
+      "bar"         => Some(Unit::Bar),
+      "atmosphere"  => Some(Unit::Atmosphere),
+      
+      _             => None,
+    }
+  }
+  
+  /// Look up a unit by an abbreviation, in lower case. Abbreviations that
+  /// are also written in the plural list it, as in 'lbs'.
+  fn from_abbreviation(name: &str) -> Option<Unit> {
+    match name {
+      "tsp" | "tsps"        => Some(Unit::Teaspoon),
+      "tbsp" | "tbsps" | "tbs" => Some(Unit::Tablespoon),
+      "qt" | "qts"          => Some(Unit::Quart),
+      "gal" | "gals"        => Some(Unit::Gallon),
+      "oz"                  => Some(Unit::Ounce),
+      "lb" | "lbs"          => Some(Unit::Pound),
+      "st"                  => Some(Unit::Stone),
+      "in"                  => Some(Unit::Inch),
+      "ft"                  => Some(Unit::Foot),
+      "yd" | "yds"          => Some(Unit::Yard),
+      "mi"                  => Some(Unit::Mile),
+      "nmi"                 => Some(Unit::NauticalMile),
+      "ha"                  => Some(Unit::Hectare),
+      "ac"                  => Some(Unit::Acre),
+      "sec" | "secs"        => Some(Unit::Second),
+      "min" | "mins"        => Some(Unit::Minute),
+      "hr" | "hrs"          => Some(Unit::Hour),
+      "wk" | "wks"          => Some(Unit::Week),
+      "mo" | "mos"          => Some(Unit::Month),
+      "yr" | "yrs"          => Some(Unit::Year),
+      "mph"                 => Some(Unit::Mph),
+      "kn" | "kt" | "kts"   => Some(Unit::Knot),
+      "hp"                  => Some(Unit::Horsepower),
+      "lbf"                 => Some(Unit::PoundForce),
+      "psi"                 => Some(Unit::Psi),
+      "atm"                 => Some(Unit::Atmosphere),
+      _                     => None,
     }
   }
   
-  pub fn ordinal(&self) -> usize {
+  /// Look up a unit by a case-sensitive symbol. Data units can't be told
+  /// apart otherwise, since 'Mb' is a megabit but 'MB' is a megabyte, and
+  /// 'C' is Celsius while 'c' is only a prefix.
+  fn from_symbol(symbol: &str) -> Option<Unit> {
+    match symbol {
+      "l" | "L"     => Some(Unit::Liter),
+      "dl" | "dL"   => Some(Unit::Deciliter),
+      "cl" | "cL"   => Some(Unit::Centiliter),
+      "ml" | "mL"   => Some(Unit::Milliliter),
+      "g"           => Some(Unit::Gram),
+      "kg"          => Some(Unit::Kilogram),
+      "t"           => Some(Unit::Tonne),
+      "mm"          => Some(Unit::Millimeter),
+      "cm"          => Some(Unit::Centimeter),
+      "m"           => Some(Unit::Meter),
+      "km"          => Some(Unit::Kilometer),
+      "°C" | "C"    => Some(Unit::Celsius),
+      "°F" | "F"    => Some(Unit::Fahrenheit),
+      "K"           => Some(Unit::Kelvin),
+      "s"           => Some(Unit::Second),
+      "h"           => Some(Unit::Hour),
+      "d"           => Some(Unit::Day),
+      "kb" | "Kb"   => Some(Unit::Kilobit),
+      "Mb"          => Some(Unit::Megabit),
+      "Gb"          => Some(Unit::Gigabit),
+      "Tb"          => Some(Unit::Terabit),
+      "B"           => Some(Unit::Byte),
+      "kB" | "KB"   => Some(Unit::Kilobyte),
+      "MB"          => Some(Unit::Megabyte),
+      "GB"          => Some(Unit::Gigabyte),
+      "TB"          => Some(Unit::Terabyte),
+      "KiB"         => Some(Unit::Kibibyte),
+      "MiB"         => Some(Unit::Mebibyte),
+      "GiB"         => Some(Unit::Gibibyte),
+      "TiB"         => Some(Unit::Tebibyte),
+      "bps"         => Some(Unit::BitPerSecond),
+      "kbps"        => Some(Unit::KilobitPerSecond),
+      "Mbps"        => Some(Unit::MegabitPerSecond),
+      "Gbps"        => Some(Unit::GigabitPerSecond),
+      "W"           => Some(Unit::Watt),
+      "Hz"          => Some(Unit::Hertz),
+      "J"           => Some(Unit::Joule),
+      "kWh"         => Some(Unit::KilowattHour),
+      "cal"         => Some(Unit::Calorie),
+      "kcal" | "Cal" => Some(Unit::Kilocalorie), // food calories
+      "N"           => Some(Unit::Newton),
+      "Pa"          => Some(Unit::Pascal),
+      _             => None,
+    }
+  }
+  
+  pub fn dimension(&self) -> Dimension {
     match self {
-      Unit::Teaspoon   => 0,
-      Unit::Tablespoon => 1,
-      Unit::Cup        => 2,
-      Unit::Quart      => 3,
-      Unit::Gallon     => 4,
-      
-      Unit::Liter      => 5,
-      Unit::Deciliter  => 6,
-      Unit::Centiliter => 7,
-      Unit::Milliliter => 8,
-      
-      Unit::Gram       => 9,
-      Unit::Kilogram   => 10,
+      Unit::Teaspoon | Unit::Tablespoon | Unit::Cup | Unit::Quart | Unit::Gallon => Dimension::Volume,
+      Unit::Liter | Unit::Deciliter | Unit::Centiliter | Unit::Milliliter => Dimension::Volume,
+      Unit::Gram | Unit::Kilogram | Unit::Tonne | Unit::Ounce | Unit::Pound | Unit::Stone | Unit::Ton => Dimension::Mass,
+      Unit::Millimeter | Unit::Centimeter | Unit::Meter | Unit::Kilometer => Dimension::Length,
+      Unit::Inch | Unit::Foot | Unit::Yard | Unit::Mile | Unit::NauticalMile => Dimension::Length,
+      Unit::Hectare | Unit::Acre => Dimension::Area,
+      Unit::Celsius | Unit::Fahrenheit | Unit::Kelvin => Dimension::Temperature,
+      Unit::Second | Unit::Minute | Unit::Hour | Unit::Day | Unit::Week | Unit::Month | Unit::Year => Dimension::Time,
+      Unit::Bit | Unit::Kilobit | Unit::Megabit | Unit::Gigabit | Unit::Terabit => Dimension::Data,
+      Unit::Byte | Unit::Kilobyte | Unit::Megabyte | Unit::Gigabyte | Unit::Terabyte => Dimension::Data,
+      Unit::Kibibyte | Unit::Mebibyte | Unit::Gibibyte | Unit::Tebibyte => Dimension::Data,
+      Unit::BitPerSecond | Unit::KilobitPerSecond | Unit::MegabitPerSecond | Unit::GigabitPerSecond => Dimension::Rate,
+      Unit::Watt => Dimension::Power,
+      Unit::Hertz => Dimension::Frequency,
+      Unit::Mph | Unit::Knot => Dimension::Speed,
+      Unit::Joule | Unit::KilowattHour | Unit::Calorie | Unit::Kilocalorie => Dimension::Energy,
+      Unit::Horsepower => Dimension::Power,
+      Unit::Newton | Unit::PoundForce => Dimension::Force,
+      Unit::Pascal | Unit::Bar | Unit::Psi | Unit::Atmosphere => Dimension::Pressure,
     }
   }
   
-  pub fn up(&self) -> Option<Unit> {
+  pub fn system(&self) -> System {
     match self {
-      Unit::Teaspoon   => Some(Unit::Tablespoon),
-      Unit::Tablespoon => Some(Unit::Cup),
-      Unit::Cup        => Some(Unit::Quart),
-      Unit::Quart      => Some(Unit::Gallon),
-      Unit::Gallon     => None,
-      
-      Unit::Milliliter => Some(Unit::Centiliter),
-      Unit::Centiliter => Some(Unit::Deciliter),
-      Unit::Deciliter  => Some(Unit::Liter),
-      Unit::Liter      => None,
-      
-      Unit::Gram       => Some(Unit::Kilogram),
-      Unit::Kilogram   => None,
+      Unit::Teaspoon | Unit::Tablespoon | Unit::Cup | Unit::Quart | Unit::Gallon => System::Customary,
+      Unit::Inch | Unit::Foot | Unit::Yard | Unit::Mile | Unit::Fahrenheit => System::Customary,
+      Unit::Ounce | Unit::Pound | Unit::Ton | Unit::Acre => System::Customary,
+      Unit::Mph | Unit::Horsepower | Unit::PoundForce | Unit::Psi => System::Customary,
+      Unit::Knot => System::Nautical,
+      Unit::Calorie | Unit::Kilocalorie | Unit::Atmosphere => System::Other,
+      Unit::Stone => System::Imperial,
+      Unit::NauticalMile => System::Nautical,
+      Unit::Kibibyte | Unit::Mebibyte | Unit::Gibibyte | Unit::Tebibyte => System::Binary,
+      Unit::Bit | Unit::Kilobit | Unit::Megabit | Unit::Gigabit | Unit::Terabit => System::Bits,
+      _ => System::Metric,
     }
   }
   
-  pub fn min(&self) -> Unit {
+  /// The size of one of this unit in the SI base unit of its dimension.
+  pub fn factor(&self) -> f64 {
     match self {
-      Unit::Teaspoon   => Unit::Teaspoon,
-      Unit::Tablespoon => Unit::Teaspoon,
-      Unit::Cup        => Unit::Teaspoon,
-      Unit::Quart      => Unit::Teaspoon,
-      Unit::Gallon     => Unit::Teaspoon,
+      Unit::Teaspoon   => 4.92892159375e-6,
+      Unit::Tablespoon => 14.78676478125e-6, // 3 tsp
+      Unit::Cup        => 236.5882365e-6,    // 16 tbsp
+      Unit::Quart      => 946.352946e-6,     // 4 cups
+      Unit::Gallon     => 3785.411784e-6,    // 4 quarts
+      
+      Unit::Liter      => 1e-3,
+      Unit::Deciliter  => 1e-4,
+      Unit::Centiliter => 1e-5,
+      Unit::Milliliter => 1e-6,
+      
+      Unit::Gram       => 1e-3,
+      Unit::Kilogram   => 1.0,
+      Unit::Tonne      => 1e3,
+      Unit::Ounce      => 0.028349523125, // 1/16 lb
+      Unit::Pound      => 0.45359237,
+      Unit::Stone      => 6.35029318,     // 14 lb
+      Unit::Ton        => 907.18474,      // 2000 lb, the US short ton
+      
+      Unit::Millimeter => 1e-3,
+      Unit::Centimeter => 1e-2,
+      Unit::Meter      => 1.0,
+      Unit::Kilometer  => 1e3,
+      Unit::Inch       => 0.0254,
+      Unit::Foot       => 0.3048,   // 12 in
+      Unit::Yard       => 0.9144,   // 3 ft
+      Unit::Mile       => 1609.344, // 1760 yd
+      Unit::NauticalMile => 1852.0,
+      
+      Unit::Hectare    => 1e4,
+      Unit::Acre       => 4046.8564224, // 4840 yd²
+      
+      Unit::Celsius    => 1.0,
+      Unit::Fahrenheit => 5.0 / 9.0,
+      Unit::Kelvin     => 1.0,
+      
+      Unit::Second     => 1.0,
+      Unit::Minute     => 60.0,
+      Unit::Hour       => 3600.0,
+      Unit::Day        => 86400.0,
+      Unit::Week       => 604800.0,
+      Unit::Month      => 2629746.0,  // 1/12 year
+      Unit::Year       => 31556952.0, // 365.2425 days
       
-      Unit::Liter      => Unit::Liter,
-      Unit::Deciliter  => Unit::Liter,
-      Unit::Centiliter => Unit::Liter,
-      Unit::Milliliter => Unit::Liter,
+      Unit::Bit        => 1.0,
+      Unit::Kilobit    => 1e3,
+      Unit::Megabit    => 1e6,
+      Unit::Gigabit    => 1e9,
+      Unit::Terabit    => 1e12,
+      Unit::Byte       => 8.0,
+      Unit::Kilobyte   => 8e3,
+      Unit::Megabyte   => 8e6,
+      Unit::Gigabyte   => 8e9,
+      Unit::Terabyte   => 8e12,
+      Unit::Kibibyte   => 8.0 * 1024.0,
+      Unit::Mebibyte   => 8.0 * 1048576.0,
+      Unit::Gibibyte   => 8.0 * 1073741824.0,
+      Unit::Tebibyte   => 8.0 * 1099511627776.0,
       
-      Unit::Gram       => Unit::Gram,
-      Unit::Kilogram   => Unit::Gram,
+      Unit::BitPerSecond     => 1.0,
+      Unit::KilobitPerSecond => 1e3,
+      Unit::MegabitPerSecond => 1e6,
+      Unit::GigabitPerSecond => 1e9,
+      
+      Unit::Watt       => 1.0,
+      Unit::Hertz      => 1.0,
+      
+      Unit::Mph        => 0.44704,
+      Unit::Knot       => 1852.0 / 3600.0, // 1 nmi/h
+      
+      Unit::Joule      => 1.0,
+      Unit::KilowattHour => 3.6e6,
+      Unit::Calorie    => 4.184,
+      Unit::Kilocalorie => 4184.0,
+      
+      Unit::Horsepower => 745.6998715822702, // 550 ft·lbf/s
+      
+      Unit::Newton     => 1.0,
+      Unit::PoundForce => 4.4482216152605,
+      
+      Unit::Pascal     => 1.0,
+      Unit::Bar        => 1e5,
+      Unit::Psi        => 6894.757293168361, // 1 lbf/in²
+      Unit::Atmosphere => 101325.0,
     }
   }
   
-  pub fn max(&self) -> Unit {
+  /// The distance of this unit's zero from absolute zero, in this unit.
+  /// Only temperatures have a zero that isn't absolute, so converting
+  /// them is a matter of shifting as well as scaling.
+  pub fn offset(&self) -> f64 {
     match self {
-      Unit::Teaspoon   => Unit::Gallon,
-      Unit::Tablespoon => Unit::Gallon,
-      Unit::Cup        => Unit::Gallon,
-      Unit::Quart      => Unit::Gallon,
-      Unit::Gallon     => Unit::Gallon,
-      
-      Unit::Liter      => Unit::Liter,
-      Unit::Deciliter  => Unit::Liter,
-      Unit::Centiliter => Unit::Liter,
-      Unit::Milliliter => Unit::Liter,
-      
-      Unit::Gram       => Unit::Kilogram,
-      Unit::Kilogram   => Unit::Kilogram,
+      Unit::Celsius    => 273.15,
+      Unit::Fahrenheit => 459.67,
+      _                => 0.0,
+    }
+  }
+  
+  /// The units this one is scaled up or down through, smallest first, or
+  /// nothing if it is the only unit of its dimension in its system.
+  fn ladder(&self) -> &'static [Unit] {
+    match LADDERS.iter().find(|l| { l.contains(self) }) {
+      Some(l) => l,
+      None => &[],
+    }
+  }
+  
+  /// The next larger unit in this unit's system, if any.
+  pub fn up(&self) -> Option<Unit> {
+    let units = self.ladder();
+    match units.iter().position(|u| { u == self }) {
+      Some(i) => units.get(i + 1).cloned(),
+      None => None,
+    }
+  }
+  
+  /// The next smaller unit in this unit's system, if any.
+  pub fn down(&self) -> Option<Unit> {
+    let units = self.ladder();
+    match units.iter().position(|u| { u == self }) {
+      Some(i) if i > 0 => units.get(i - 1).cloned(),
+      _ => None,
+    }
+  }
+  
+  /// Whether SI prefixes can be applied to this unit, as in mg or kW.
+  /// Units with a prefix already, and those outside the SI, cannot.
+  pub fn is_prefixable(&self) -> bool {
+    match self {
+      Unit::Meter | Unit::Gram | Unit::Liter | Unit::Second | Unit::Watt | Unit::Hertz => true,
+      Unit::Joule | Unit::Newton | Unit::Pascal => true,
+      _ => false,
     }
   }
   
-  pub fn is_convertable(&self, to: Unit) -> bool {
-    CONVERSION[self.ordinal()][to.ordinal()] != 0.0
+  /// The base dimensions this unit measures, with their powers. Areas
+  /// and volumes are lengths squared and cubed, so that m³ is also a
+  /// volume; rates are data over time.
+  fn signature(&self) -> Vec<(Dimension, i32)> {
+    match self.dimension() {
+      Dimension::Area => vec![(Dimension::Length, 2)],
+      Dimension::Volume => vec![(Dimension::Length, 3)],
+      Dimension::Rate => vec![(Dimension::Data, 1), (Dimension::Time, -1)],
+      Dimension::Power => vec![(Dimension::Mass, 1), (Dimension::Length, 2), (Dimension::Time, -3)],
+      Dimension::Frequency => vec![(Dimension::Time, -1)],
+      Dimension::Speed => vec![(Dimension::Length, 1), (Dimension::Time, -1)],
+      Dimension::Energy => vec![(Dimension::Mass, 1), (Dimension::Length, 2), (Dimension::Time, -2)],
+      Dimension::Force => vec![(Dimension::Mass, 1), (Dimension::Length, 1), (Dimension::Time, -2)],
+      Dimension::Pressure => vec![(Dimension::Mass, 1), (Dimension::Length, -1), (Dimension::Time, -2)],
+      dim => vec![(dim, 1)],
+    }
   }
 }
 
+/// The units of each dimension in each system, smallest first, which
+/// values are scaled up and down through. Temperature scales are not
+/// multiples of one another, so a temperature is never scaled into
+/// another unit.
+const LADDERS: [&[Unit]; 14] = [
+  &[Unit::Teaspoon, Unit::Tablespoon, Unit::Cup, Unit::Quart, Unit::Gallon],
+  &[Unit::Milliliter, Unit::Centiliter, Unit::Deciliter, Unit::Liter],
+  &[Unit::Gram, Unit::Kilogram, Unit::Tonne],
+  &[Unit::Ounce, Unit::Pound, Unit::Ton],
+  &[Unit::Millimeter, Unit::Centimeter, Unit::Meter, Unit::Kilometer],
+  &[Unit::Inch, Unit::Foot, Unit::Yard, Unit::Mile],
+  &[Unit::Second, Unit::Minute, Unit::Hour, Unit::Day, Unit::Week, Unit::Month, Unit::Year],
+  &[Unit::Bit, Unit::Kilobit, Unit::Megabit, Unit::Gigabit, Unit::Terabit],
+  &[Unit::Byte, Unit::Kilobyte, Unit::Megabyte, Unit::Gigabyte, Unit::Terabyte],
+  &[Unit::Kibibyte, Unit::Mebibyte, Unit::Gibibyte, Unit::Tebibyte],
+  &[Unit::BitPerSecond, Unit::KilobitPerSecond, Unit::MegabitPerSecond, Unit::GigabitPerSecond],
+  &[Unit::Joule, Unit::KilowattHour],
+  &[Unit::Calorie, Unit::Kilocalorie],
+  &[Unit::Pascal, Unit::Bar],
+];
+
 impl fmt::Display for Unit {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
@@ -149,14 +580,660 @@ impl fmt::Display for Unit {
       
       Self::Gram       => write!(f, "{}", "g"),
       Self::Kilogram   => write!(f, "{}", "kg"),
+      Self::Tonne      => write!(f, "{}", "t"),
+      Self::Ounce      => write!(f, "{}", "oz"),
+      Self::Pound      => write!(f, "{}", "lb"),
+      Self::Stone      => write!(f, "{}", "st"),
+      Self::Ton        => write!(f, "{}", "ton"),
+      
+      Self::Millimeter => write!(f, "{}", "mm"),
+      Self::Centimeter => write!(f, "{}", "cm"),
+      Self::Meter      => write!(f, "{}", "m"),
+      Self::Kilometer  => write!(f, "{}", "km"),
+      Self::Inch       => write!(f, "{}", "in"),
+      Self::Foot       => write!(f, "{}", "ft"),
+      Self::Yard       => write!(f, "{}", "yd"),
+      Self::Mile       => write!(f, "{}", "mi"),
+      Self::NauticalMile => write!(f, "{}", "nmi"),
+      
+      Self::Hectare    => write!(f, "{}", "ha"),
+      Self::Acre       => write!(f, "{}", "acre"),
+      
+      Self::Celsius    => write!(f, "{}", "°C"),
+      Self::Fahrenheit => write!(f, "{}", "°F"),
+      Self::Kelvin     => write!(f, "{}", "K"),
+      
+      Self::Second     => write!(f, "{}", "s"),
+      Self::Minute     => write!(f, "{}", "min"),
+      Self::Hour       => write!(f, "{}", "h"),
+      Self::Day        => write!(f, "{}", "d"),
+      Self::Week       => write!(f, "{}", "wk"),
+      Self::Month      => write!(f, "{}", "mo"),
+      Self::Year       => write!(f, "{}", "yr"),
+      
+      Self::Bit        => write!(f, "{}", "bit"),
+      Self::Kilobit    => write!(f, "{}", "kb"),
+      Self::Megabit    => write!(f, "{}", "Mb"),
+      Self::Gigabit    => write!(f, "{}", "Gb"),
+      Self::Terabit    => write!(f, "{}", "Tb"),
+      Self::Byte       => write!(f, "{}", "B"),
+      Self::Kilobyte   => write!(f, "{}", "KB"),
+      Self::Megabyte   => write!(f, "{}", "MB"),
+      Self::Gigabyte   => write!(f, "{}", "GB"),
+      Self::Terabyte   => write!(f, "{}", "TB"),
+      Self::Kibibyte   => write!(f, "{}", "KiB"),
+      Self::Mebibyte   => write!(f, "{}", "MiB"),
+      Self::Gibibyte   => write!(f, "{}", "GiB"),
+      Self::Tebibyte   => write!(f, "{}", "TiB"),
+      
+      Self::BitPerSecond     => write!(f, "{}", "bps"),
+      Self::KilobitPerSecond => write!(f, "{}", "kbps"),
+      Self::MegabitPerSecond => write!(f, "{}", "Mbps"),
+      Self::GigabitPerSecond => write!(f, "{}", "Gbps"),
+      
+      Self::Watt       => write!(f, "{}", "W"),
+      Self::Hertz      => write!(f, "{}", "Hz"),
+      
+      Self::Mph        => write!(f, "{}", "mph"),
+      Self::Knot       => write!(f, "{}", "kn"),
+      
+      Self::Joule      => write!(f, "{}", "J"),
+      Self::KilowattHour => write!(f, "{}", "kWh"),
+      Self::Calorie    => write!(f, "{}", "cal"),
+      Self::Kilocalorie => write!(f, "{}", "kcal"),
+      
+      Self::Horsepower => write!(f, "{}", "hp"),
+      
+      Self::Newton     => write!(f, "{}", "N"),
+      Self::PoundForce => write!(f, "{}", "lbf"),
+      
+      Self::Pascal     => write!(f, "{}", "Pa"),
+      Self::Bar        => write!(f, "{}", "bar"),
+      Self::Psi        => write!(f, "{}", "psi"),
+      Self::Atmosphere => write!(f, "{}", "atm"),
     }
   }
 }
 
+/// An SI prefix, which scales the unit it is applied to.
 #[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Prefix {
+  Pico,
+  Nano,
+  Micro,
+  Milli,
+  Centi,
+  Deci,
+  Hecto,
+  Kilo,
+  Mega,
+  Giga,
+  Tera,
+}
+
+impl Prefix {
+  pub const ALL: [Prefix; 11] = [
+    Prefix::Pico, Prefix::Nano, Prefix::Micro, Prefix::Milli, Prefix::Centi,
+    Prefix::Deci, Prefix::Hecto, Prefix::Kilo, Prefix::Mega, Prefix::Giga, Prefix::Tera,
+  ];
+  
+  /// Split a prefix from the start of a unit name, as in 'kW'. Prefixes
+  /// are case-sensitive, since 'm' is milli but 'M' is mega.
+  fn split(name: &str) -> Option<(Prefix, &str)> {
+    for prefix in Prefix::ALL {
+      for symbol in prefix.symbols() {
+        if let Some(rest) = name.strip_prefix(symbol) {
+          return Some((prefix, rest));
+        }
+      }
+    }
+    None
+  }
+  
+  /// Split a prefix from the start of a unit name written out in full,
+  /// as in 'kilowatts', regardless of case.
+  fn split_name(name: &str) -> Option<(Prefix, String)> {
+    let name = name.to_lowercase();
+    for prefix in Prefix::ALL {
+      if let Some(rest) = name.strip_prefix(prefix.name()) {
+        return Some((prefix, rest.to_string()));
+      }
+    }
+    None
+  }
+  
+  fn name(&self) -> &'static str {
+    match self {
+      Prefix::Pico  => "pico",
+      Prefix::Nano  => "nano",
+      Prefix::Micro => "micro",
+      Prefix::Milli => "milli",
+      Prefix::Centi => "centi",
+      Prefix::Deci  => "deci",
+      Prefix::Hecto => "hecto",
+      Prefix::Kilo  => "kilo",
+      Prefix::Mega  => "mega",
+      Prefix::Giga  => "giga",
+      Prefix::Tera  => "tera",
+    }
+  }
+  
+  fn symbols(&self) -> &'static [&'static str] {
+    match self {
+      Prefix::Pico  => &["p"],
+      Prefix::Nano  => &["n"],
+      Prefix::Micro => &["µ", "μ", "u"],
+      Prefix::Milli => &["m"],
+      Prefix::Centi => &["c"],
+      Prefix::Deci  => &["d"],
+      Prefix::Hecto => &["h"],
+      Prefix::Kilo  => &["k"],
+      Prefix::Mega  => &["M"],
+      Prefix::Giga  => &["G"],
+      Prefix::Tera  => &["T"],
+    }
+  }
+  
+  pub fn factor(&self) -> f64 {
+    match self {
+      Prefix::Pico  => 1e-12,
+      Prefix::Nano  => 1e-9,
+      Prefix::Micro => 1e-6,
+      Prefix::Milli => 1e-3,
+      Prefix::Centi => 1e-2,
+      Prefix::Deci  => 1e-1,
+      Prefix::Hecto => 1e2,
+      Prefix::Kilo  => 1e3,
+      Prefix::Mega  => 1e6,
+      Prefix::Giga  => 1e9,
+      Prefix::Tera  => 1e12,
+    }
+  }
+}
+
+impl fmt::Display for Prefix {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.symbols()[0])
+  }
+}
+
+/// A unit as it appears in a compound, with an optional SI prefix. The
+/// units with a name of their own, like km or ml, are never prefixed;
+/// others, like mg or GHz, are a prefix applied to a base unit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Prefixed {
+  prefix: Option<Prefix>,
+  unit: Unit,
+}
+
+impl Prefixed {
+  pub fn new(prefix: Option<Prefix>, unit: Unit) -> Prefixed {
+    Prefixed{
+      prefix: prefix,
+      unit: unit,
+    }
+  }
+  
+  /// Look up a unit that may carry a prefix. Units are first matched as
+  /// written, then with a prefix symbol, which like the symbols it is
+  /// applied to is case-sensitive, then by name regardless of case and
+  /// then with a prefix name, as in 'kilowatts', and finally as a symbol in
+  /// the wrong case, if only one unit could be meant. So 'kN' is a
+  /// kilonewton but 'kn' and 'Kn' are knots, 'Mg' is a megagram and 'KG'
+  /// is a kilogram, but 'gb' is nothing, since it could be Gb or GB.
+  pub fn from(name: &str) -> Option<Prefixed> {
+    let name = name.trim();
+    if let Some(unit) = Unit::from_written(name) {
+      return Some(Prefixed::new(None, unit));
+    }
+    if let Some((prefix, rest)) = Prefix::split(name) {
+      if let Some(unit) = Unit::from_symbol(rest).filter(|u| { u.is_prefixable() }) {
+        return Some(Prefixed::new(Some(prefix), unit));
+      }
+    }
+    if let Some(unit) = Unit::from_name(&name.to_lowercase()) {
+      return Some(Prefixed::new(None, unit));
+    }
+    if let Some((prefix, rest)) = Prefix::split_name(name) {
+      if let Some(unit) = Unit::from_name(&rest).filter(|u| { u.is_prefixable() }) {
+        return Some(Prefixed::new(Some(prefix), unit));
+      }
+    }
+    match Prefixed::from_any_case(name).as_slice() {
+      [unit] => Some(*unit),
+      _ => None,
+    }
+  }
+  
+  /// Every unit a symbol of two or more letters could mean when its case
+  /// is ignored, as in 'KM'. Symbols of their own are preferred to those
+  /// with a prefix, so 'MM' is a millimeter rather than a megameter.
+  pub fn from_any_case(name: &str) -> Vec<Prefixed> {
+    let chars: Vec<char> = name.trim().chars().collect();
+    if chars.len() < 2 || chars.len() > 5 {
+      return Vec::new();
+    }
+    let mut whole: Vec<Prefixed> = Vec::new();
+    let mut prefixed: Vec<Prefixed> = Vec::new();
+    for mask in 0..(1u32 << chars.len()) {
+      let variant: String = chars.iter().enumerate().flat_map(|(i, c)| {
+        if mask & (1 << i) != 0 { c.to_uppercase().collect::<Vec<char>>() } else { c.to_lowercase().collect() }
+      }).collect();
+      if let Some(unit) = Unit::from_symbol(&variant) {
+        let unit = Prefixed::new(None, unit);
+        if !whole.contains(&unit) {
+          whole.push(unit);
+        }
+      }
+      if let Some((prefix, rest)) = Prefix::split(&variant) {
+        if let Some(unit) = Unit::from_symbol(rest).filter(|u| { u.is_prefixable() }) {
+          let unit = Prefixed::new(Some(prefix), unit);
+          if !prefixed.contains(&unit) {
+            prefixed.push(unit);
+          }
+        }
+      }
+    }
+    if whole.is_empty() {
+      prefixed
+    }else{
+      whole
+    }
+  }
+  
+  pub fn factor(&self) -> f64 {
+    match self.prefix {
+      Some(prefix) => prefix.factor() * self.unit.factor(),
+      None => self.unit.factor(),
+    }
+  }
+  
+  pub fn dimension(&self) -> Dimension {
+    self.unit.dimension()
+  }
+}
+
+impl fmt::Display for Prefixed {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.prefix {
+      Some(prefix) => write!(f, "{}{}", prefix, self.unit),
+      None => write!(f, "{}", self.unit),
+    }
+  }
+}
+
+/// A unit declared in a document, as in 'unit sprint = 2 weeks'. It is a
+/// multiple of the quantity it was declared as, or when declared from a
+/// word that is not a unit, as 'bottle' is in 'unit crate = 24 bottles',
+/// a base unit of a dimension of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Custom {
+  name: String,
+  factor: f64,
+  signature: Vec<(Dimension, i32)>,
+}
+
+impl Custom {
+  /// Whether this unit is known by `name`, either as it was declared or
+  /// with its plural added or removed, so that 'sprint' is also 'sprints'.
+  /// Names as short as a symbol have no plural, so that a unit 'p' does not
+  /// take over 'ps'.
+  fn is_named(&self, name: &str) -> bool {
+    let plural = |a: &str, b: &str| {
+      b.chars().count() > 2 && (a.strip_suffix('s') == Some(b) || a.strip_suffix("es") == Some(b))
+    };
+    self.name == name || plural(name, &self.name) || plural(&self.name, name)
+  }
+}
+
+impl fmt::Display for Custom {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.name)
+  }
+}
+
+/// The units declared in a document so far. A unit declared again
+/// replaces the earlier one for what follows.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+  units: Vec<Custom>,
+  dimensions: usize,
+}
+
+impl Registry {
+  /// Declare a base unit of a new dimension.
+  pub fn declare_base(&mut self, name: &str) {
+    let dim = Dimension::Custom(self.dimensions);
+    self.dimensions += 1;
+    self.insert(Custom{
+      name: name.to_string(),
+      factor: 1.0,
+      signature: vec![(dim, 1)],
+    });
+  }
+  
+  /// Declare a unit of a built-in dimension as a multiple of its base
+  /// unit, as a currency is of the base currency of the exchange rates.
+  pub fn declare_unit(&mut self, name: &str, factor: f64, dim: Dimension) {
+    self.insert(Custom{
+      name: name.to_string(),
+      factor: factor,
+      signature: vec![(dim, 1)],
+    });
+  }
+  
+  /// Forget every unit declared in the dimension `dim`.
+  pub fn clear(&mut self, dim: Dimension) {
+    self.units.retain(|u| { u.signature != [(dim, 1)] });
+  }
+  
+  /// Declare a unit as a multiple of another quantity.
+  pub fn declare(&mut self, name: &str, def: &Value) {
+    let (factor, signature) = match &def.unit {
+      Some(unit) => (unit.factor(), unit.signature()),
+      None => (1.0, Vec::new()),
+    };
+    self.insert(Custom{
+      name: name.to_string(),
+      factor: def.value * factor,
+      signature: signature,
+    });
+  }
+  
+  fn insert(&mut self, unit: Custom) {
+    self.units.retain(|u| { u.name != unit.name });
+    self.units.push(unit);
+  }
+  
+  pub fn get(&self, name: &str) -> Option<&Custom> {
+    self.units.iter().rev().find(|u| { u.is_named(name) })
+  }
+  
+  /// Look up a currency by its code regardless of case, as in 'eur'. This
+  /// is only done for names that are not built-in units, so that 'cup' is
+  /// never the Cuban peso.
+  fn currency(&self, name: &str) -> Option<&Custom> {
+    self.units.iter().rev().find(|u| {
+      u.signature == [(Dimension::Money, 1)] && u.name.eq_ignore_ascii_case(name)
+    })
+  }
+}
+
+/// A term of a compound unit, which is either a built-in unit or one
+/// declared in the document.
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+  Unit(Prefixed),
+  Custom(Custom),
+}
+
+impl Term {
+  fn factor(&self) -> f64 {
+    match self {
+      Term::Unit(unit) => unit.factor(),
+      Term::Custom(unit) => unit.factor,
+    }
+  }
+  
+  /// The dimension of a built-in unit. Declared units have none, so they
+  /// are never merged with another unit in a product.
+  fn dimension(&self) -> Option<Dimension> {
+    match self {
+      Term::Unit(unit) => Some(unit.dimension()),
+      Term::Custom(_) => None,
+    }
+  }
+  
+  fn signature(&self) -> Vec<(Dimension, i32)> {
+    match self {
+      Term::Unit(unit) => unit.unit.signature(),
+      Term::Custom(unit) => unit.signature.clone(),
+    }
+  }
+}
+
+impl fmt::Display for Term {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Term::Unit(unit) => unit.fmt(f),
+      Term::Custom(unit) => unit.fmt(f),
+    }
+  }
+}
+
+/// A product of units raised to integer powers, as in km/h or kg². No
+/// unit appears in more than one term, and terms whose powers cancel out
+/// are dropped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound {
+  terms: Vec<(Term, i32)>,
+}
+
+impl Compound {
+  #[cfg(test)]
+  pub fn new(terms: Vec<(Unit, i32)>) -> Compound {
+    let mut c = Compound::default();
+    for (unit, exp) in terms {
+      c.push(Term::Unit(Prefixed::new(None, unit)), exp);
+    }
+    c
+  }
+  
+  /// Parse a compound unit as it is written in a document, as in 'km/h',
+  /// 'm^2', 'm²' or 'kg*m/s²'. Every term after a '/' is a divisor.
+  pub fn from(name: &str) -> Option<Compound> {
+    Compound::from_with(name, &Registry::default())
+  }
+  
+  /// Parse a compound unit that may include units declared in the
+  /// document, which take precedence over built-in ones.
+  pub fn from_with(name: &str, units: &Registry) -> Option<Compound> {
+    let mut c = Compound::default();
+    for (i, part) in name.split('/').enumerate() {
+      let sign = if i > 0 { -1 } else { 1 };
+      for term in part.split(|c| { c == '*' || c == '·' }) {
+        let term = term.trim();
+        if i == 0 && term == "1" {
+          continue; // as in '1/s'
+        }
+        let (name, exp) = Compound::exponent(term)?;
+        let unit = match units.get(name.trim()) {
+          Some(unit) => Term::Custom(unit.clone()),
+          None => match Prefixed::from(name) {
+            Some(unit) => Term::Unit(unit),
+            None => Term::Custom(units.currency(name.trim())?.clone()),
+          },
+        };
+        c.push(unit, sign * exp);
+      }
+    }
+    if c.is_empty() {
+      None
+    }else{
+      Some(c)
+    }
+  }
+  
+  /// Split a term into its unit and exponent, as in 'm^2' or 'm²'.
+  fn exponent(term: &str) -> Option<(&str, i32)> {
+    if let Some((name, exp)) = term.split_once('^') {
+      return match exp.trim().parse::<i32>() {
+        Ok(exp) => Some((name, exp)),
+        Err(_) => None,
+      };
+    }
+    if let Some(name) = term.strip_suffix('²') {
+      Some((name, 2))
+    }else if let Some(name) = term.strip_suffix('³') {
+      Some((name, 3))
+    }else{
+      Some((term, 1))
+    }
+  }
+  
+  fn push(&mut self, unit: Term, exp: i32) {
+    match self.terms.iter().position(|(u, _)| { *u == unit }) {
+      Some(i) => {
+        self.terms[i].1 += exp;
+        if self.terms[i].1 == 0 {
+          self.terms.remove(i);
+        }
+      },
+      None => if exp != 0 {
+        self.terms.push((unit, exp));
+      },
+    }
+  }
+  
+  pub fn is_empty(&self) -> bool {
+    self.terms.len() == 0
+  }
+  
+  /// The unit this compound consists of, if it is just one without a
+  /// prefix.
+  pub fn single(&self) -> Option<Unit> {
+    match self.terms.as_slice() {
+      [(Term::Unit(Prefixed{ prefix: None, unit }), 1)] => Some(*unit),
+      _ => None,
+    }
+  }
+  
+  /// The size of one of this compound in SI base units.
+  pub fn factor(&self) -> f64 {
+    self.terms.iter().map(|(u, e)| { u.factor().powi(*e) }).product()
+  }
+  
+  /// The base dimensions this compound measures, with their powers.
+  fn signature(&self) -> Vec<(Dimension, i32)> {
+    let mut sig: Vec<(Dimension, i32)> = Vec::new();
+    for (unit, exp) in &self.terms {
+      for (dim, e) in unit.signature() {
+        match sig.iter().position(|(d, _)| { *d == dim }) {
+          Some(i) => sig[i].1 += e * exp,
+          None => sig.push((dim, e * exp)),
+        }
+      }
+    }
+    sig.retain(|(_, e)| { *e != 0 });
+    sig
+  }
+  
+  /// Whether this compound is an amount of money, in some currency.
+  pub fn is_money(&self) -> bool {
+    self.signature() == [(Dimension::Money, 1)]
+  }
+  
+  /// The currency terms of this compound, as in USD for 'USD/h', which
+  /// are empty if it has nothing to do with money.
+  pub fn money(&self) -> Compound {
+    Compound{
+      terms: self.terms.iter().filter(|(u, _)| { u.signature() == [(Dimension::Money, 1)] }).cloned().collect(),
+    }
+  }
+  
+  pub fn is_convertable(&self, to: &Compound) -> bool {
+    same_signature(&self.signature(), &to.signature())
+  }
+  
+  /// Raise this compound to a power, which must leave every term with a
+  /// whole exponent, so that the root of m² is m but the root of m is
+  /// not defined.
+  pub fn pow(&self, exp: f64) -> Option<Compound> {
+    let mut c = Compound::default();
+    for (unit, e) in &self.terms {
+      let e = *e as f64 * exp;
+      if e.fract() != 0.0 {
+        return None;
+      }
+      c.push(unit.clone(), e as i32);
+    }
+    Some(c)
+  }
+  
+  /// Combine this compound with another raised to `sign`, which is 1 for
+  /// a product and -1 for a quotient. Units of the other compound that
+  /// measure a dimension this one already has a unit for are replaced by
+  /// that unit; the value of the other must be scaled by the factor that
+  /// is produced alongside the result.
+  fn combine(&self, other: &Compound, sign: i32) -> (Compound, f64) {
+    let mut res = self.clone();
+    let mut scale = 1.0;
+    for (unit, exp) in &other.terms {
+      let into = match self.terms.iter().find(|(u, _)| { u.dimension().is_some() && u.dimension() == unit.dimension() }) {
+        Some((into, _)) => into.clone(),
+        None => unit.clone(),
+      };
+      scale *= (unit.factor() / into.factor()).powi(*exp);
+      res.push(into, sign * exp);
+    }
+    (res, scale)
+  }
+  
+  /// When some of the dimensions of this compound cancel out, as in
+  /// TB/Mbps or l/m, produce the base unit of what remains, or a compound
+  /// of base units when there is no such unit, as m² is for l/m, and the
+  /// factor to scale a value by. Compounds like km/h are left alone.
+  fn simplify(&self) -> Option<(Option<Compound>, f64)> {
+    let sig = self.signature();
+    if sig.len() >= self.terms.len() {
+      return None;
+    }
+    if sig.len() == 0 {
+      return Some((None, self.factor()));
+    }
+    let base = |sig: &[(Dimension, i32)]| {
+      Unit::ALL.iter().find(|u| { u.factor() == 1.0 && same_signature(&u.signature(), sig) }).cloned()
+    };
+    if let Some(unit) = base(&sig) {
+      return Some((Some(Compound::from_unit(unit)), self.factor()));
+    }
+    let mut c = Compound::default();
+    for (dim, exp) in &sig {
+      c.push(Term::Unit(Prefixed::new(None, base(&[(*dim, 1)])?)), *exp);
+    }
+    Some((Some(c), self.factor()))
+  }
+  
+  pub fn from_unit(unit: Unit) -> Compound {
+    Compound{
+      terms: vec![(Term::Unit(Prefixed::new(None, unit)), 1)],
+    }
+  }
+}
+
+fn same_signature(a: &[(Dimension, i32)], b: &[(Dimension, i32)]) -> bool {
+  a.len() == b.len() && a.iter().all(|t| { b.contains(t) })
+}
+
+impl fmt::Display for Compound {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let term = |unit: &Term, exp: i32| {
+      match exp {
+        1 => unit.to_string(),
+        2 => format!("{}²", unit),
+        3 => format!("{}³", unit),
+        _ => format!("{}^{}", unit, exp),
+      }
+    };
+    let num: Vec<String> = self.terms.iter().filter(|(_, e)| { *e > 0 }).map(|(u, e)| { term(u, *e) }).collect();
+    let den: Vec<String> = self.terms.iter().filter(|(_, e)| { *e < 0 }).map(|(u, e)| { term(u, -e) }).collect();
+    let num = if num.len() > 0 { num.join("·") } else { "1".to_string() };
+    if den.len() > 0 {
+      write!(f, "{}/{}", num, den.join("·"))
+    }else{
+      write!(f, "{}", num)
+    }
+  }
+}
+
+/// A quantity, with an optional unit. A quantity of an ingredient, as
+/// in '1 cup flour', carries the ingredient so that it can be converted
+/// between volume and mass.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Value {
   value: f64,
-  unit: Option<Unit>,
+  unit: Option<Compound>,
+  ingredient: Option<Ingredient>,
 }
 
 impl Value {
@@ -164,27 +1241,32 @@ impl Value {
     Value{
       value: v,
       unit: None,
+      ingredient: None,
     }
   }
   
+  #[cfg(test)]
   pub fn new(v: f64, u: Unit) -> Value {
     Value{
       value: v,
-      unit: Some(u),
+      unit: Some(Compound::from_unit(u)),
+      ingredient: None,
     }
   }
   
-  pub fn option(v: f64, u: Option<Unit>) -> Value {
+  pub fn option(v: f64, u: Option<Compound>) -> Value {
     Value{
       value: v,
-      unit: u,
+      unit: u.filter(|u| { !u.is_empty() }),
+      ingredient: None,
     }
   }
   
-  pub fn untype(&self) -> Value {
+  pub fn with_ingredient(&self, ingredient: Option<Ingredient>) -> Value {
     Value{
       value: self.value,
-      unit: None,
+      unit: self.unit.clone(),
+      ingredient: ingredient,
     }
   }
   
@@ -192,51 +1274,91 @@ impl Value {
     self.value
   }
   
-  pub fn unit(&self) -> Option<Unit> {
-    self.unit
+  pub fn unit(&self) -> Option<&Compound> {
+    self.unit.as_ref()
   }
   
-  pub fn is_compatible(&self, with: Option<Unit>) -> bool {
-    match self.unit {
-      None      => true,
-      Some(a)   => match with {
-        None    => true,
-        Some(b) => a.is_convertable(b),
-      }
+  /// Raise this value to the power of another. The exponent must be
+  /// unitless, and must leave the unit of this value with whole powers,
+  /// as in (3 m)^2; None is produced otherwise rather than silently
+  /// dropping the unit.
+  pub fn pow(&self, exp: Value) -> Option<Value> {
+    if exp.unit.is_some() {
+      return None;
     }
+    let unit = match &self.unit {
+      None => None,
+      Some(unit) => Some(unit.pow(exp.value)?),
+    };
+    Some(Value::option(self.value.powf(exp.value), unit))
   }
   
   pub fn convert(&self, to: Option<Unit>) -> Option<Value> {
+    self.convert_compound(to.map(Compound::from_unit).as_ref())
+  }
+  
+  /// Convert this value to a compound unit. A temperature on its own is
+  /// shifted as well as scaled; within a compound, as in °C/h, it is a
+  /// difference and is only scaled.
+  pub fn convert_compound(&self, to: Option<&Compound>) -> Option<Value> {
     let to = match to {
       Some(to) => to,
       None => return Some(Value::raw(self.value)),
     };
-    let from = match self.unit {
+    let from = match &self.unit {
       Some(from) => from,
-      None => return Some(Value::new(self.value, to)),
+      None => return Some(Value::option(self.value, Some(to.clone()))),
     };
     if from == to {
-      return Some(*self);
+      return Some(self.clone());
     }
-    let factor = CONVERSION[from.ordinal()][to.ordinal()];
-    if factor == 0.0 {
-      None // cannot convert
-    }else{
-      Some(Value::new(self.value * factor, to))
+    if !from.is_convertable(to) {
+      return self.convert_density(from, to);
     }
+    let (a, b) = match (from.single(), to.single()) {
+      (Some(a), Some(b)) => (a.offset(), b.offset()),
+      _ => (0.0, 0.0),
+    };
+    let base = (self.value + a) * from.factor();
+    Some(Value::option(normalize(base / to.factor() - b), Some(to.clone())).with_ingredient(self.ingredient))
   }
   
-  fn base(&self) -> Value {
-    match self.unit {
-      None       => *self,
-      Some(unit) => self.convert(Some(unit.min())).unwrap(),
+  /// Convert between volume and mass through the density of this value's
+  /// ingredient, as in '1 cup flour in g'.
+  fn convert_density(&self, from: &Compound, to: &Compound) -> Option<Value> {
+    let density = self.ingredient?.density();
+    let volume = Compound::from_unit(Unit::Liter);
+    let mass = Compound::from_unit(Unit::Kilogram);
+    let base = self.value * from.factor();
+    let base = if from.is_convertable(&volume) && to.is_convertable(&mass) {
+      base * density
+    }else if from.is_convertable(&mass) && to.is_convertable(&volume) {
+      base / density
+    }else{
+      return None;
+    };
+    Some(Value::option(normalize(base / to.factor()), Some(to.clone())).with_ingredient(self.ingredient))
+  }
+  
+  /// Convert this value as a difference rather than an absolute quantity,
+  /// which only scales it: a change of 9 °F is a change of 5 °C, even
+  /// though 9 °F is -12.78 °C.
+  pub fn interval(&self, to: Unit) -> Option<Value> {
+    let to = Compound::from_unit(to);
+    let from = match &self.unit {
+      Some(from) => from,
+      None => return Some(Value::option(self.value, Some(to))),
+    };
+    if !from.is_convertable(&to) {
+      return None;
     }
+    Some(Value::option(normalize(self.value * from.factor() / to.factor()), Some(to)))
   }
   
   fn pack(&self) -> Value {
-    let mut v = *self;
+    let mut v = self.clone();
     loop {
-      let c = match v.unit {
+      let c = match v.unit.as_ref().and_then(|u| { u.single() }) {
         Some(c) => c,
         None => return v,
       };
@@ -254,54 +1376,191 @@ impl Value {
       }
     }
   }
+  
+  /// Format this value for reading, in the largest unit it fills. In
+  /// customary and imperial units the remainder is written as a fraction
+  /// of a cup or spoon, or split across smaller units, as in '1 1/2 cups',
+  /// '1 cup 2 tbsp' or '5 ft 3 in'.
+  pub fn to_packed(&self) -> String {
+    let unit = match self.unit.as_ref().and_then(|u| { u.single() }) {
+      Some(unit) => unit,
+      None => return self.to_string(),
+    };
+    if unit.dimension() == Dimension::Temperature {
+      return self.to_string();
+    }
+    match unit.system() {
+      System::Customary | System::Imperial => format_mixed(self.value, unit),
+      _ => self.pack().to_string(),
+    }
+  }
+  
+  /// Format this value for reading in its own unit, which is how a value
+  /// converted into a unit is packed. Once it fills one of its unit, the
+  /// remainder is split across smaller units, as in '2 hours 15 min', but
+  /// it is never moved into a larger unit.
+  pub fn to_mixed(&self) -> String {
+    let unit = match self.unit.as_ref().and_then(|u| { u.single() }) {
+      Some(unit) => unit,
+      None => return self.to_string(),
+    };
+    if self.value.abs() < 1.0 || unit.dimension() == Dimension::Temperature {
+      return self.to_decimal();
+    }
+    if unit.dimension() == Dimension::Time {
+      return format_duration(self.value, unit);
+    }
+    match unit.system() {
+      System::Customary | System::Imperial => split_mixed(self.value, unit),
+      _ => self.to_decimal(),
+    }
+  }
+  
+  /// Format this value as a decimal in its own unit, which is how a value
+  /// converted into a unit is displayed, so that a duration reads '2.25
+  /// hours' rather than '2 hours 15 min'.
+  pub fn to_decimal(&self) -> String {
+    match self.unit.as_ref().and_then(|u| { u.single() }) {
+      Some(unit) if unit.dimension() == Dimension::Time => {
+        let n = if countable(self.value * unit.factor()) {
+          normalize((self.value * unit.factor() * 1000.0).round() / 1000.0 / unit.factor())
+        }else{
+          self.value
+        };
+        format!("{} {}", n, duration_name(unit, n))
+      },
+      _ => self.to_string(),
+    }
+  }
 }
 
-fn operands(left: Value, right: Value) -> (Option<Unit>, Value, Value) {
-  let target = util::coalesce(right.unit, left.unit);
-  let left = match left.convert(target) {
-    Some(conv) => conv,
-    None => left.untype(),
-  };
-  let right = match right.convert(target) {
-    Some(conv) => conv,
-    None => right.untype(),
+/// Round a converted value to 12 significant digits, which hides the
+/// error introduced by scaling through the base unit, so that 3 tsp is
+/// exactly 1 tbsp. Every conversion is rounded this way, so a converted
+/// value never has more than 12 significant digits, even when the value
+/// it was converted from does. The rounding goes through the decimal
+/// form of the value, since scaling a tiny value up by a power of ten and
+/// back down again brings the error back, as in 1 pm in m.
+fn normalize(v: f64) -> f64 {
+  if v == 0.0 || !v.is_finite() {
+    return v;
+  }
+  match format!("{:.11e}", v).parse() {
+    Ok(n) => n,
+    Err(_) => v,
+  }
+}
+
+/// Convert the operands of a sum to the same unit, which is the unit of
+/// the right one unless it has none. Quantities that cannot be converted
+/// into one another, like 3 kg and 2 cups, cannot be summed.
+fn operands(left: Value, right: Value) -> Result<(Option<Compound>, Value, Value), error::Error> {
+  let target = util::coalesce(right.unit.clone(), left.unit.clone());
+  match (left.convert_compound(target.as_ref()), right.convert_compound(target.as_ref())) {
+    (Some(l), Some(r)) => Ok((target, l, r)),
+    _ => Err(mismatch(&left, &right)),
+  }
+}
+
+/// Quantities of two different ingredients are summed by mass, each
+/// converted through its own density, since a cup of flour and a cup of
+/// sugar are not two cups of either. The sum is of no one ingredient.
+fn mixture(left: Value, right: Value) -> Result<(Value, Value), error::Error> {
+  match (left.ingredient, right.ingredient) {
+    (Some(a), Some(b)) if a != b => {},
+    _ => return Ok((left, right)),
   };
-  (target, left, right)
+  let mass = Compound::from_unit(Unit::Gram);
+  let target = [&right.unit, &left.unit].into_iter().flatten().find(|u| { u.is_convertable(&mass) }).cloned().unwrap_or(mass);
+  match (left.convert_compound(Some(&target)), right.convert_compound(Some(&target))) {
+    (Some(l), Some(r)) => Ok((l.with_ingredient(None), r.with_ingredient(None))),
+    _ => Err(mismatch(&left, &right)),
+  }
+}
+
+/// The error for two quantities whose units cannot be converted into
+/// one another, as in 3 kg and 2 cups.
+pub fn mismatch(left: &Value, right: &Value) -> error::Error {
+  let name = |v: &Value| { v.unit.as_ref().map(|u| { u.to_string() }).unwrap_or_default() };
+  error::Error::UnitMismatch(name(left), name(right))
+}
+
+/// Absolute temperatures cannot be summed, so when adding or subtracting
+/// temperatures the right operand is taken as a difference and applied
+/// to the left one, in its unit: 20 °C + 5 °C is 25 °C and 20 °C + 9 °F
+/// is also 25 °C.
+fn temperatures(left: &Value, right: &Value) -> Option<(Option<Compound>, Value, Value)> {
+  let a = left.unit.as_ref()?.single()?;
+  let b = right.unit.as_ref()?.single()?;
+  if a.dimension() != Dimension::Temperature || b.dimension() != Dimension::Temperature {
+    return None;
+  }
+  Some((left.unit.clone(), left.clone(), right.interval(a)?))
 }
 
 impl ops::Add<Value> for Value {
-  type Output = Value;
+  type Output = Result<Value, error::Error>;
   
-  fn add(self, right: Value) -> Value {
-    let (target, left, right) = operands(self, right);
-    Value{
+  fn add(self, right: Value) -> Self::Output {
+    let (left, right) = mixture(self, right)?;
+    let ingredient = util::coalesce(left.ingredient, right.ingredient);
+    let (target, left, right) = match temperatures(&left, &right) {
+      Some(ops) => ops,
+      None => operands(left, right)?,
+    };
+    Ok(Value{
       value: left.value + right.value,
       unit: target,
-    }
+      ingredient: ingredient,
+    })
   }
 }
 
 impl ops::Sub<Value> for Value {
-  type Output = Value;
+  type Output = Result<Value, error::Error>;
   
-  fn sub(self, right: Value) -> Value {
-    let (target, left, right) = operands(self, right);
-    Value{
+  fn sub(self, right: Value) -> Self::Output {
+    let (left, right) = mixture(self, right)?;
+    let ingredient = util::coalesce(left.ingredient, right.ingredient);
+    let (target, left, right) = match temperatures(&left, &right) {
+      Some(ops) => ops,
+      None => operands(left, right)?,
+    };
+    Ok(Value{
       value: left.value - right.value,
       unit: target,
-    }
+      ingredient: ingredient,
+    })
   }
 }
 
+/// Multiply or divide two values, as `sign` is 1 or -1, producing a
+/// compound unit, so 2 kg * 3 kg is 6 kg² and 100 km / 2 h is 50 km/h.
+/// Units on the right are converted to a unit on the left that measures
+/// the same thing, so 2 m * 30 cm is 0.6 m². Units that partly cancel
+/// out leave the base unit of what remains, so 2 TB / 100 Mbps is a time.
+/// Scaling a quantity of an ingredient by a number keeps the ingredient.
+fn product(left: Value, right: Value, sign: i32) -> Value {
+  let ingredient = match (&left.unit, &right.unit) {
+    (_, None) => left.ingredient,
+    (None, _) => right.ingredient,
+    _ => None,
+  };
+  let (unit, scale) = left.unit.clone().unwrap_or_default().combine(&right.unit.clone().unwrap_or_default(), sign);
+  let r = if scale != 1.0 { normalize(right.value * scale) } else { right.value };
+  let value = if sign > 0 { left.value * r } else { left.value / r };
+  let res = match unit.simplify() {
+    Some((unit, factor)) => Value::option(normalize(value * factor), unit),
+    None => Value::option(value, Some(unit)),
+  };
+  res.with_ingredient(ingredient)
+}
+
 impl ops::Mul<Value> for Value {
   type Output = Value;
   
   fn mul(self, right: Value) -> Value {
-    let (target, left, right) = operands(self, right);
-    Value{
-      value: left.value * right.value,
-      unit: target,
-    }
+    product(self, right, 1)
   }
 }
 
@@ -309,35 +1568,53 @@ impl ops::Div<Value> for Value {
   type Output = Value;
   
   fn div(self, right: Value) -> Value {
-    let (target, left, right) = operands(self, right);
-    Value{
-      value: left.value / right.value,
-      unit: target,
-    }
+    product(self, right, -1)
   }
 }
 
 impl ops::Rem<Value> for Value {
-  type Output = Value;
+  type Output = Result<Value, error::Error>;
   
-  fn rem(self, right: Value) -> Value {
-    let (target, left, right) = operands(self, right);
-    Value{
+  fn rem(self, right: Value) -> Self::Output {
+    let ingredient = self.ingredient;
+    let (target, left, right) = operands(self, right)?;
+    Ok(Value{
       value: left.value % right.value,
       unit: target,
+      ingredient: ingredient,
+    })
+  }
+}
+
+impl ops::Neg for Value {
+  type Output = Value;
+  
+  fn neg(self) -> Value {
+    Value{
+      value: -self.value,
+      unit: self.unit,
+      ingredient: self.ingredient,
     }
   }
 }
 
 impl fmt::Display for Value {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(unit) = self.unit.as_ref().and_then(|u| { u.single() }) {
+      if unit.dimension() == Dimension::Time {
+        return write!(f, "{}", format_duration(self.value, unit));
+      }
+    }
+    if let Some(unit) = self.unit.as_ref().filter(|u| { u.is_money() }) {
+      return write!(f, "{:.2} {}", self.value, unit); // in cents
+    }
     if f.alternate() {
-      match self.unit {
+      match &self.unit {
         Some(unit) => write!(f, "{} {}", format_qty(self.value), unit),
         None       => write!(f, "{}", format_qty(self.value)),
       }
     }else{
-      match self.unit {
+      match &self.unit {
         Some(unit) => write!(f, "{} {}", self.value, unit),
         None       => write!(f, "{}", self.value),
       }
@@ -345,6 +1622,155 @@ impl fmt::Display for Value {
   }
 }
 
+/// The name of a duration unit as it is written in mixed form, where
+/// most units read better spelled out.
+fn duration_name(unit: Unit, n: f64) -> String {
+  let name = match unit {
+    Unit::Second => return "sec".to_string(),
+    Unit::Minute => return "min".to_string(),
+    Unit::Hour   => "hour",
+    Unit::Day    => "day",
+    Unit::Week   => "week",
+    Unit::Month  => "month",
+    Unit::Year   => "year",
+    _            => return unit.to_string(),
+  };
+  if n == 1.0 {
+    name.to_string()
+  }else{
+    format!("{}s", name)
+  }
+}
+
+/// Whether a duration of this many seconds can be counted in whole
+/// milliseconds, which hides the error left by converting it. Durations
+/// shorter than a millisecond cannot, and neither can those too long to be
+/// counted in milliseconds exactly.
+fn countable(secs: f64) -> bool {
+  let secs = secs.abs();
+  secs.is_finite() && (secs == 0.0 || secs >= 0.001) && secs * 1000.0 < 2f64.powi(53)
+}
+
+/// Format a duration in mixed form, breaking any fraction of its unit
+/// down into smaller units, so that 3.75 hours is '3 hours 45 min'. The
+/// duration is broken down in whole milliseconds; durations that cannot
+/// be counted that way are written as plain numbers instead.
+fn format_duration(n: f64, unit: Unit) -> String {
+  let ms = |v: f64| { (v * 1000.0).round() / 1000.0 };
+  let secs = n.abs() * unit.factor();
+  if !countable(secs) {
+    return format!("{} {}", n + 0.0, unit); // adding zero turns -0 into 0
+  }
+  let mut parts: Vec<String> = Vec::new();
+  let mut rem = ms(secs); // in seconds
+  let mut unit = unit;
+  loop {
+    let smaller = match unit.down() {
+      Some(smaller) => smaller,
+      None => {
+        if rem != 0.0 || parts.len() == 0 {
+          let rem = ms(rem);
+          parts.push(format!("{} {}", rem, duration_name(unit, rem)));
+        }
+        break;
+      },
+    };
+    let whole = (rem / unit.factor()).floor();
+    if whole > 0.0 {
+      parts.push(format!("{} {}", whole, duration_name(unit, whole)));
+      rem -= whole * unit.factor();
+    }
+    if rem == 0.0 && parts.len() > 0 {
+      break;
+    }
+    unit = smaller;
+  }
+  let text = parts.join(" ");
+  if n < 0.0 {
+    format!("-{}", text)
+  }else{
+    text
+  }
+}
+
+/// The name of a unit as it is written in mixed form, where the units
+/// that are spelled out take a plural.
+fn mixed_name(unit: Unit, n: f64) -> String {
+  match unit {
+    Unit::Cup | Unit::Quart | Unit::Gallon | Unit::Ton if n > 1.0 => format!("{}s", unit),
+    _ => unit.to_string(),
+  }
+}
+
+/// The next larger and smaller units in mixed form. Yards are skipped,
+/// since lengths read as feet and inches.
+fn mixed_up(unit: Unit) -> Option<Unit> {
+  match unit.up() {
+    Some(Unit::Yard) => Unit::Yard.up(),
+    up => up,
+  }
+}
+
+fn mixed_down(unit: Unit) -> Option<Unit> {
+  match unit.down() {
+    Some(Unit::Yard) => Unit::Yard.down(),
+    down => down,
+  }
+}
+
+/// Format a quantity in mixed form, in the largest unit it fills.
+fn format_mixed(n: f64, unit: Unit) -> String {
+  let mut n = n;
+  let mut unit = unit;
+  while let Some(larger) = mixed_up(unit) {
+    let m = normalize(n * unit.factor() / larger.factor());
+    if m.abs() < 1.0 {
+      break;
+    }
+    n = m;
+    unit = larger;
+  }
+  split_mixed(n, unit)
+}
+
+/// Format a quantity in mixed form, in the unit it is in. A quarter, half
+/// or three quarters of a volume is kept as a fraction, as it would be
+/// measured; any other remainder is broken down into the next smaller
+/// unit, unless that takes more than 16 of them, so that a mile is never
+/// split into feet.
+fn split_mixed(n: f64, unit: Unit) -> String {
+  let negative = n < 0.0;
+  let mut n = n.abs();
+  let mut unit = unit;
+  let mut parts: Vec<String> = Vec::new();
+  loop {
+    let whole = n.floor();
+    let frac = normalize(n - whole);
+    let measured = unit.dimension() == Dimension::Volume && [0.25, 0.5, 0.75].contains(&frac);
+    let smaller = match mixed_down(unit) {
+      Some(smaller) if frac != 0.0 && !measured && unit.factor() / smaller.factor() < 16.5 => smaller,
+      _ => {
+        let n = (n * 1000.0).round() / 1000.0;
+        if n != 0.0 || parts.len() == 0 {
+          parts.push(format!("{} {}", format_qty(n), mixed_name(unit, n)));
+        }
+        break;
+      },
+    };
+    if whole > 0.0 {
+      parts.push(format!("{} {}", whole, mixed_name(unit, whole)));
+    }
+    n = normalize(frac * unit.factor() / smaller.factor());
+    unit = smaller;
+  }
+  let text = parts.join(" ");
+  if negative {
+    format!("-{}", text)
+  }else{
+    text
+  }
+}
+
 fn to_fraction(n: f64) -> Option<String> {
   if n == 0.125 {
     Some("1/8".to_string())
@@ -382,35 +1808,6 @@ fn format_qty(n: f64) -> String {
 mod tests {
   use super::*;
   
-  #[test]
-  fn to_base() {
-    assert_eq!(Value::new(3.0, Unit::Teaspoon), Value::new(3.0, Unit::Teaspoon).base());
-    
-    assert_eq!(Value::new(3.0, Unit::Teaspoon), Value::new(1.0, Unit::Tablespoon).base());
-    assert_eq!(Value::new(48.0, Unit::Teaspoon), Value::new(1.0, Unit::Cup).base());
-    assert_eq!(Value::new(192.0, Unit::Teaspoon), Value::new(1.0, Unit::Quart).base());
-    assert_eq!(Value::new(768.0, Unit::Teaspoon), Value::new(1.0, Unit::Gallon).base());
-    
-    assert_eq!(Value::new(12.0, Unit::Teaspoon), Value::new(0.25, Unit::Cup).base());
-    assert_eq!(Value::new(24.0, Unit::Teaspoon), Value::new(0.5, Unit::Cup).base());
-    assert_eq!(Value::new(24.0, Unit::Teaspoon), Value::new(0.125, Unit::Quart).base());
-    assert_eq!(Value::new(24.0, Unit::Teaspoon), Value::new(8.0, Unit::Tablespoon).base());
-
-    assert_eq!(Value::new(0.25, Unit::Liter), Value::new(0.25, Unit::Liter).base());
-    assert_eq!(Value::new(0.1, Unit::Liter), Value::new(1.0, Unit::Deciliter).base());
-    assert_eq!(Value::new(0.01, Unit::Liter), Value::new(1.0, Unit::Centiliter).base());
-    assert_eq!(Value::new(0.001, Unit::Liter), Value::new(1.0, Unit::Milliliter).base());
-    assert_eq!(Value::new(1.0, Unit::Liter), Value::new(10.0, Unit::Deciliter).base());
-    assert_eq!(Value::new(1.0, Unit::Liter), Value::new(100.0, Unit::Centiliter).base());
-    assert_eq!(Value::new(1.0, Unit::Liter), Value::new(1000.0, Unit::Milliliter).base());
-    assert_eq!(Value::new(3.1, Unit::Liter), Value::new(3100.0, Unit::Milliliter).base());
-
-    assert_eq!(Value::new(10.0, Unit::Gram), Value::new(10.0, Unit::Gram).base());
-    assert_eq!(Value::new(1000.0, Unit::Gram), Value::new(1000.0, Unit::Gram).base());
-    assert_eq!(Value::new(1000.0, Unit::Gram), Value::new(1.0, Unit::Kilogram).base());
-    assert_eq!(Value::new(2000.0, Unit::Gram), Value::new(2.0, Unit::Kilogram).base());
-  }
-  
   #[test]
   fn to_pack() {
     assert_eq!(Value::new(2.0, Unit::Teaspoon), Value::new(2.0, Unit::Teaspoon).pack());
@@ -471,6 +1868,281 @@ mod tests {
     assert_eq!("2 kg", &format!("{:#}", Value::new(2.0, Unit::Kilogram).pack()));
   }
   
+  #[test]
+  fn to_packed() {
+    assert_eq!("5 ft 3 in", Value::new(63.0, Unit::Inch).to_packed());
+    assert_eq!("5 ft 3 in", Value::new(5.25, Unit::Foot).to_packed());
+    assert_eq!("2 yd", Value::new(2.0, Unit::Yard).to_packed());
+    assert_eq!("1 1/2 mi", Value::new(7920.0, Unit::Foot).to_packed());
+    assert_eq!("-5 ft 3 in", Value::new(-63.0, Unit::Inch).to_packed());
+    
+    assert_eq!("1 cup 2 tbsp", Value::new(18.0, Unit::Tablespoon).to_packed());
+    assert_eq!("1 1/2 cups", Value::new(24.0, Unit::Tablespoon).to_packed());
+    assert_eq!("1/2 quart", Value::new(0.5, Unit::Quart).to_packed());
+    assert_eq!("1 tbsp 1 tsp", Value::new(4.0, Unit::Teaspoon).to_packed());
+    assert_eq!("1/2 tsp", Value::new(0.5, Unit::Teaspoon).to_packed());
+    assert_eq!("1 1/4 gallons", Value::new(5.0, Unit::Quart).to_packed());
+    
+    assert_eq!("1 lb 8 oz", Value::new(24.0, Unit::Ounce).to_packed());
+    assert_eq!("1.5 kg", Value::new(1500.0, Unit::Gram).to_packed());
+    assert_eq!("1 hour 30 min", Value::new(90.0, Unit::Minute).to_packed());
+    assert_eq!("98.6 °F", Value::new(98.6, Unit::Fahrenheit).to_packed());
+    assert_eq!("2.5", Value::raw(2.5).to_packed());
+  }
+  
+  #[test]
+  fn durations() {
+    assert_eq!("3 hours 45 min", Value::new(3.75, Unit::Hour).to_string());
+    assert_eq!("1 hour 30 min", Value::new(90.0, Unit::Minute).convert(Some(Unit::Hour)).unwrap().to_string());
+    assert_eq!("2 hours 15 min", Value::new(2.25, Unit::Hour).to_string());
+    assert_eq!("90 min", Value::new(90.0, Unit::Minute).to_string());
+    assert_eq!("1 day", Value::new(1.0, Unit::Day).to_string());
+    assert_eq!("1 week 3 days 12 hours", Value::new(1.5, Unit::Week).to_string());
+    assert_eq!("30 min", Value::new(0.5, Unit::Hour).to_string());
+    assert_eq!("0 sec", Value::new(0.0, Unit::Second).to_string());
+    assert_eq!("-1 hour 6 min", Value::new(-1.1, Unit::Hour).to_string());
+    assert_eq!("1.5 sec", Value::new(1.5, Unit::Second).to_string());
+    assert_eq!("0.0004 s", Value::new(0.0004, Unit::Second).to_string());
+    assert_eq!("-0.0001 s", Value::new(-0.0001, Unit::Second).to_string());
+    assert_eq!("0 sec", Value::new(-0.0, Unit::Second).to_string());
+    assert_eq!(format!("{} h", 1e300), Value::new(1e300, Unit::Hour).to_string());
+    assert_eq!("30 days 10 hours 29 min 6 sec", Value::new(1.0, Unit::Month).convert(Some(Unit::Day)).unwrap().to_string());
+    
+    assert_eq!(Some(Value::new(2.25, Unit::Hour)), Value::new(135.0, Unit::Minute).convert(Some(Unit::Hour)));
+    assert_eq!(Some(Value::new(168.0, Unit::Hour)), Value::new(1.0, Unit::Week).convert(Some(Unit::Hour)));
+    assert_eq!(Some(Value::new(12.0, Unit::Month)), Value::new(1.0, Unit::Year).convert(Some(Unit::Month)));
+  }
+  
+  #[test]
+  fn compound() {
+    assert_eq!(Some(Compound::new(vec![(Unit::Kilometer, 1), (Unit::Hour, -1)])), Compound::from("km/h"));
+    assert_eq!(Some(Compound::new(vec![(Unit::Meter, 2)])), Compound::from("m²"));
+    assert_eq!(Some(Compound::new(vec![(Unit::Meter, 2)])), Compound::from("m^2"));
+    assert_eq!(Some(Compound::new(vec![(Unit::Kilogram, 1), (Unit::Meter, 1), (Unit::Second, -2)])), Compound::from("kg*m/s²"));
+    assert_eq!(Some(Compound::new(vec![(Unit::Second, -1)])), Compound::from("1/s"));
+    assert_eq!(None, Compound::from("km/parsec"));
+    assert_eq!(None, Compound::from("m/m"));
+    
+    assert_eq!("km/h", Compound::from("km/h").unwrap().to_string());
+    assert_eq!("kg·m/s²", Compound::from("kg*m/s^2").unwrap().to_string());
+    assert_eq!("1/s", Compound::from("1/s").unwrap().to_string());
+    assert_eq!("50 km/h", Value::option(50.0, Compound::from("km/h")).to_string());
+    
+    let mph = Compound::from("mi/h");
+    assert_eq!(Some(Value::option(96.56064, Compound::from("km/h"))), Value::option(60.0, mph).convert_compound(Compound::from("km/h").as_ref()));
+    assert_eq!(None, Value::option(1.0, Compound::from("km/h")).convert_compound(Compound::from("km").as_ref()));
+    assert_eq!(Some(Value::option(54.0, Compound::from("°F/h"))), Value::option(0.5, Compound::from("°C/min")).convert_compound(Compound::from("°F/h").as_ref()));
+  }
+  
+  #[test]
+  fn areas() {
+    let unit = |name: &str| { Compound::from(name) };
+    
+    assert_eq!(Some(Value::new(2.47105381467, Unit::Acre)), Value::new(1.0, Unit::Hectare).convert(Some(Unit::Acre)));
+    assert_eq!(Some(Value::new(1.0, Unit::Hectare)), Value::option(10000.0, unit("m²")).convert(Some(Unit::Hectare)));
+    assert_eq!(Some(Value::option(43560.0, unit("ft²"))), Value::new(1.0, Unit::Acre).convert_compound(unit("ft^2").as_ref()));
+    assert_eq!(Some(Value::option(10000.0, unit("cm²"))), Value::option(1.0, unit("m²")).convert_compound(unit("cm²").as_ref()));
+    assert_eq!(None, Value::option(1.0, unit("m²")).convert(Some(Unit::Liter)));
+    
+    assert_eq!(Some(Value::new(3000.0, Unit::Liter)), Value::option(3.0, unit("m³")).convert(Some(Unit::Liter)));
+    assert_eq!(Some(Value::new(7.48051948052, Unit::Gallon)), Value::option(1.0, unit("ft³")).convert(Some(Unit::Gallon)));
+    assert_eq!(Some(Value::option(1.0, unit("m³"))), Value::new(1000.0, Unit::Liter).convert_compound(unit("m^3").as_ref()));
+    
+    assert_eq!(Value::option(3.0, unit("m³")), Value::new(2.0, Unit::Meter) * Value::new(3.0, Unit::Meter) * Value::new(0.5, Unit::Meter));
+    assert_eq!(Value::option(0.06, unit("m²")), Value::new(2.0, Unit::Meter) * Value::new(3.0, Unit::Centimeter));
+    assert_eq!(Value::option(5.0, unit("m²")), Value::new(10.0, Unit::Liter) / Value::new(2.0, Unit::Millimeter));
+    assert_eq!(Value::new(2.0, Unit::Meter), Value::option(6.0, unit("m³")) / Value::option(3.0, unit("m²")));
+  }
+  
+  #[test]
+  fn engineering() {
+    let unit = |name: &str| { Compound::from(name) };
+    let convert = |v: f64, from: &str, to: &str| {
+      Value::option(v, unit(from)).convert_compound(unit(to).as_ref()).map(|v| { v.value() })
+    };
+    
+    assert_eq!(Some(100.0), convert(27.7777777778, "m/s", "km/h"));
+    assert_eq!(Some(96.56064), convert(60.0, "mph", "km/h"));
+    assert_eq!(Some(18.52), convert(10.0, "kn", "km/h"));
+    assert_eq!(Some(1.0), convert(1.0, "kn", "nmi/h"));
+    
+    assert_eq!(Some(3.6), convert(1.0, "kWh", "MJ"));
+    assert_eq!(Some(4184.0), convert(1.0, "kcal", "J"));
+    assert_eq!(Some(1.0), convert(1.0, "Cal", "kcal"));
+    assert_eq!(Some(1000.0), convert(1.0, "kcal", "cal"));
+    assert_eq!(Some(3.0), convert(3.0, "kW*h", "kWh"));
+    assert_eq!(Some(1.0), convert(1.0, "J", "N*m"));
+    
+    assert_eq!(Some(74.5699871582), convert(100.0, "hp", "kW"));
+    assert_eq!(Some(1.0), convert(1.0, "W", "J/s"));
+    
+    assert_eq!(Some(4.44822161526), convert(1.0, "lbf", "N"));
+    assert_eq!(Some(1.0), convert(1.0, "N", "kg*m/s²"));
+    
+    assert_eq!(Some(1013.25), convert(1.0, "atm", "hPa"));
+    assert_eq!(Some(14.503773773), convert(1.0, "bar", "psi"));
+    assert_eq!(Some(1.0), convert(1.0, "Pa", "N/m²"));
+    assert_eq!(None, convert(1.0, "Pa", "J"));
+    
+    assert_eq!(Some(Prefixed::new(Some(Prefix::Kilo), Unit::Newton)), Prefixed::from("kN"));
+    assert_eq!(Some(Prefixed::new(None, Unit::Knot)), Prefixed::from("kn"));
+    assert_eq!(Some(Prefixed::new(Some(Prefix::Hecto), Unit::Pascal)), Prefixed::from("hPa"));
+    assert_eq!(Some(Prefixed::new(None, Unit::Hectare)), Prefixed::from("ha"));
+  }
+  
+  #[test]
+  fn custom() {
+    let mut units = Registry::default();
+    units.declare("sprint", &Value::new(2.0, Unit::Week));
+    units.declare_base("bottle");
+    let unit = |name: &str| { Compound::from_with(name, &units) };
+    units.declare("crate", &Value::option(24.0, unit("bottle")));
+    let unit = |name: &str| { Compound::from_with(name, &units) };
+    
+    assert!(unit("sprints").is_some());
+    assert!(unit("bottles").is_some());
+    assert!(unit("sprint/day").is_some());
+    assert_eq!(None, Compound::from("sprint"));
+    
+    assert_eq!(Some(Value::new(140.0, Unit::Day)), Value::option(10.0, unit("sprints")).convert(Some(Unit::Day)));
+    assert_eq!(Some(Value::option(72.0, unit("bottle"))), Value::option(3.0, unit("crate")).convert_compound(unit("bottle").as_ref()));
+    assert_eq!(None, Value::option(3.0, unit("crate")).convert(Some(Unit::Kilogram)));
+    assert_eq!("3 crate", Value::option(3.0, unit("crates")).to_string());
+    
+    units.declare_base("p");
+    assert!(Compound::from_with("p", &units).is_some());
+    assert_eq!(Compound::from("ps"), Compound::from_with("ps", &units)); // still picoseconds
+  }
+  
+  #[test]
+  fn ingredients() {
+    let flour = |v: f64, u: Unit| { Value::new(v, u).with_ingredient(Some(Ingredient::Flour)) };
+    assert_eq!(Some(flour(120.0, Unit::Gram)), flour(1.0, Unit::Cup).convert(Some(Unit::Gram)));
+    assert_eq!(Some(flour(2.0, Unit::Cup)), flour(240.0, Unit::Gram).convert(Some(Unit::Cup)));
+    assert_eq!(None, Value::new(1.0, Unit::Cup).convert(Some(Unit::Gram)));
+    assert_eq!(None, flour(1.0, Unit::Cup).convert(Some(Unit::Meter)));
+    
+    assert_eq!(Ok(flour(240.0, Unit::Gram)), flour(1.0, Unit::Cup) + flour(120.0, Unit::Gram));
+    let sugar = |v: f64, u: Unit| { Value::new(v, u).with_ingredient(Some(Ingredient::Sugar)) };
+    assert_eq!(Ok(Value::new(318.0, Unit::Gram)), flour(1.0, Unit::Cup) + sugar(1.0, Unit::Cup));
+    assert_eq!(Ok(Value::new(78.0, Unit::Gram)), sugar(1.0, Unit::Cup) - flour(1.0, Unit::Cup));
+    assert_eq!(Ok(Value::new(318.0, Unit::Gram)), flour(120.0, Unit::Gram) + sugar(1.0, Unit::Cup));
+    assert_eq!(flour(3.0, Unit::Cup), flour(1.5, Unit::Cup) * Value::raw(2.0));
+    assert_eq!(Value::new(1.0, Unit::Cup) * Value::new(1.0, Unit::Cup), flour(1.0, Unit::Cup) * Value::new(1.0, Unit::Cup));
+    
+    let water = Value::new(1.0, Unit::Liter).with_ingredient(Ingredient::from("water"));
+    assert_eq!(Some(Value::new(1.0, Unit::Kilogram).with_ingredient(Some(Ingredient::Water))), water.convert(Some(Unit::Kilogram)));
+  }
+  
+  #[test]
+  fn names() {
+    assert_eq!(Some(Unit::Kilogram), Unit::from("kilograms"));
+    assert_eq!(Some(Unit::Kilogram), Unit::from("Kilos"));
+    assert_eq!(Some(Unit::Liter), Unit::from("litres"));
+    assert_eq!(Some(Unit::Liter), Unit::from("Liters"));
+    assert_eq!(Some(Unit::Milliliter), Unit::from("millilitre"));
+    assert_eq!(Some(Unit::Tablespoon), Unit::from("tablespoons"));
+    assert_eq!(Some(Unit::Tablespoon), Unit::from("TBSP"));
+    assert_eq!(Some(Unit::Inch), Unit::from("inches"));
+    assert_eq!(Some(Unit::Foot), Unit::from("Feet"));
+    assert_eq!(Some(Unit::Gram), Unit::from("grammes"));
+    assert_eq!(Some(Unit::Hour), Unit::from("hrs"));
+    assert_eq!(Some(Unit::Celsius), Unit::from("Celsius"));
+    assert_eq!(None, Unit::from("has")); // abbreviations have no plural of their own
+    assert_eq!(None, Unit::from("feets"));
+    
+    assert_eq!(Some(Unit::Meter), Unit::from("m"));
+    assert_eq!(None, Unit::from("M"));
+    assert_eq!(Some(Unit::Liter), Unit::from("L"));
+    assert_eq!(Some(Unit::Celsius), Unit::from("C"));
+    assert_eq!(None, Unit::from("c")); // only a prefix
+    assert_eq!(Some(Unit::Kelvin), Unit::from("K"));
+    assert_eq!(None, Unit::from("k")); // only a prefix
+    assert_eq!(Some(Unit::Tonne), Unit::from("t"));
+    assert_eq!(Some(Unit::Tonne), Unit::from("tonnes"));
+    assert_eq!(Some(Unit::Ton), Unit::from("tons"));
+    assert_eq!(Some(Unit::Knot), Unit::from("kn"));
+    assert_eq!(Some(Unit::Knot), Unit::from("Kn"));
+    
+    assert_eq!(Some(Value::new(2.0, Unit::Ton)), Value::new(4000.0, Unit::Pound).convert(Some(Unit::Ton)));
+    assert_eq!(Some(Value::new(1.5, Unit::Tonne)), Value::new(1500.0, Unit::Kilogram).convert(Some(Unit::Tonne)));
+    assert_eq!("1 1/2 tons", Value::new(3000.0, Unit::Pound).to_packed());
+    assert_eq!("5 t", Value::new(5000.0, Unit::Kilogram).to_packed());
+  }
+  
+  #[test]
+  fn prefixes() {
+    let prefixed = |p: Prefix, u: Unit| { Some(Prefixed::new(Some(p), u)) };
+    assert_eq!(prefixed(Prefix::Milli, Unit::Gram), Prefixed::from("mg"));
+    assert_eq!(prefixed(Prefix::Micro, Unit::Liter), Prefixed::from("µl"));
+    assert_eq!(prefixed(Prefix::Micro, Unit::Liter), Prefixed::from("ul"));
+    assert_eq!(prefixed(Prefix::Mega, Unit::Gram), Prefixed::from("Mg"));
+    assert_eq!(prefixed(Prefix::Kilo, Unit::Watt), Prefixed::from("kW"));
+    assert_eq!(prefixed(Prefix::Giga, Unit::Hertz), Prefixed::from("GHz"));
+    assert_eq!(prefixed(Prefix::Nano, Unit::Second), Prefixed::from("ns"));
+    assert_eq!(prefixed(Prefix::Mega, Unit::Meter), Prefixed::from("Mm"));
+    assert_eq!(Some(Prefixed::new(None, Unit::Milliliter)), Prefixed::from("ml"));
+    assert_eq!(Some(Prefixed::new(None, Unit::Kilogram)), Prefixed::from("KG"));
+    assert_eq!(Some(Prefixed::new(None, Unit::Kilometer)), Prefixed::from("KM"));
+    assert_eq!(Some(Prefixed::new(None, Unit::Millimeter)), Prefixed::from("MM"));
+    assert_eq!(prefixed(Prefix::Mega, Unit::Liter), Prefixed::from("ML")); // as written
+    assert_eq!(prefixed(Prefix::Kilo, Unit::Watt), Prefixed::from("KW"));
+    assert_eq!(None, Prefixed::from("gb")); // a gigabit or a gigabyte
+    assert_eq!(None, Prefixed::from("MS")); // a millisecond or a megasecond
+    assert_eq!(Some(Prefixed::new(None, Unit::Minute)), Prefixed::from("min"));
+    assert_eq!(None, Prefixed::from("kday"));
+    assert_eq!(None, Prefixed::from("mtsp"));
+    
+    assert_eq!(prefixed(Prefix::Kilo, Unit::Newton), Prefixed::from("kN"));
+    assert_eq!(prefixed(Prefix::Kilo, Unit::Watt), Prefixed::from("kilowatts"));
+    assert_eq!(prefixed(Prefix::Mega, Unit::Hertz), Prefixed::from("Megahertz"));
+    assert_eq!(prefixed(Prefix::Micro, Unit::Liter), Prefixed::from("microlitres"));
+    assert_eq!(None, Prefixed::from("kilotons")); // not an SI unit
+    
+    assert_eq!("mg", Prefixed::from("mg").unwrap().to_string());
+    assert_eq!("µl", Prefixed::from("ul").unwrap().to_string());
+    assert_eq!("kW", Prefixed::from("kilowatt").unwrap().to_string());
+    
+    let conv = |v: f64, from: &str, to: &str| {
+      Value::option(v, Compound::from(from)).convert_compound(Compound::from(to).as_ref()).map(|v| { v.value() })
+    };
+    assert_eq!(Some(1500.0), conv(1.5, "g", "mg"));
+    assert_eq!(Some(2.0), conv(2000.0, "kg", "Mg"));
+    assert_eq!(Some(250.0), conv(0.25, "ml", "µl"));
+    assert_eq!(Some(2400.0), conv(2.4, "GHz", "MHz"));
+    assert_eq!(Some(1.5), conv(1500.0, "W", "kW"));
+    assert_eq!(Some(0.25), conv(250.0, "ms", "s"));
+    assert_eq!(Some(3.6e6), conv(1.0, "kW*h", "W*s"));
+    assert_eq!(Some(1e-12), conv(1.0, "pm", "m"));
+    assert_eq!(Some(3e-9), conv(3.0, "ns", "s"));
+    assert_eq!(Some(2.5e-3), conv(2.5, "nm", "µm"));
+    assert_eq!(Some(4e3), conv(4.0, "ns", "ps"));
+    assert_eq!(None, conv(1.0, "kW", "GHz"));
+  }
+  
+  #[test]
+  fn data() {
+    assert_eq!(Some(Unit::Megabit), Unit::from("Mb"));
+    assert_eq!(Some(Unit::Megabyte), Unit::from("MB"));
+    assert_eq!(Some(Unit::Mebibyte), Unit::from("MiB"));
+    assert_eq!(Some(Unit::Megabyte), Unit::from("megabytes"));
+    assert_eq!(None, Unit::from("mb"));
+    
+    assert_eq!(Some(Value::new(8.0, Unit::Megabit)), Value::new(1.0, Unit::Megabyte).convert(Some(Unit::Megabit)));
+    assert_eq!(Some(Value::new(1024.0, Unit::Kibibyte)), Value::new(1.0, Unit::Mebibyte).convert(Some(Unit::Kibibyte)));
+    assert_eq!(Some(Value::new(1.073741824, Unit::Gigabyte)), Value::new(1.0, Unit::Gibibyte).convert(Some(Unit::Gigabyte)));
+    assert_eq!(Some(Value::new(1000.0, Unit::Megabit)), Value::new(1.0, Unit::Gigabit).convert(Some(Unit::Megabit)));
+    assert_eq!(None, Value::new(1.0, Unit::Megabyte).convert(Some(Unit::MegabitPerSecond)));
+    assert_eq!(Some(Unit::Gigabyte), Unit::Megabyte.up());
+    assert_eq!(Some(Unit::Gibibyte), Unit::Mebibyte.up());
+    assert_eq!(Some(Unit::Gigabit), Unit::Megabit.up());
+    
+    assert_eq!(Value::new(160000.0, Unit::Second), Value::new(2.0, Unit::Terabyte) / Value::new(100.0, Unit::MegabitPerSecond));
+    assert_eq!(Some(Value::new(8e6, Unit::BitPerSecond)), (Value::new(60.0, Unit::Megabyte) / Value::new(1.0, Unit::Minute)).convert(Some(Unit::BitPerSecond)));
+    assert_eq!(Value::new(6e8, Unit::Bit), Value::new(10.0, Unit::MegabitPerSecond) * Value::new(1.0, Unit::Minute));
+    assert_eq!(Value::new(6e8, Unit::Bit), Value::new(1.0, Unit::Minute) * Value::new(10.0, Unit::MegabitPerSecond));
+  }
+  
   #[test]
   fn convert() {
     assert_eq!(Some(Value::raw(1.0)), Value::new(1.0, Unit::Tablespoon).convert(None));
@@ -481,16 +2153,108 @@ mod tests {
     assert_eq!(Some(Value::new(5.0, Unit::Teaspoon)), Value::raw(5.0).convert(Some(Unit::Teaspoon)));
     assert_eq!(Some(Value::new(15.0, Unit::Teaspoon)), Value::new(5.0, Unit::Tablespoon).convert(Some(Unit::Teaspoon)));
     assert_eq!(Some(Value::new(1.0, Unit::Cup)), Value::new(16.0, Unit::Tablespoon).convert(Some(Unit::Cup)));
-    assert_eq!(Some(Value::new(0.236588395339208, Unit::Liter)), Value::new(16.0, Unit::Tablespoon).convert(Some(Unit::Liter)));
+    assert_eq!(Some(Value::new(0.2365882365, Unit::Liter)), Value::new(16.0, Unit::Tablespoon).convert(Some(Unit::Liter)));
+    assert_eq!(Some(Value::new(236.5882365, Unit::Milliliter)), Value::new(1.0, Unit::Cup).convert(Some(Unit::Milliliter)));
+    assert_eq!(Some(Value::new(202.884136211, Unit::Teaspoon)), Value::new(1.0, Unit::Liter).convert(Some(Unit::Teaspoon)));
+    assert_eq!(Some(Value::new(2.5, Unit::Kilogram)), Value::new(2500.0, Unit::Gram).convert(Some(Unit::Kilogram)));
+    assert_eq!(Some(Value::new(16.0, Unit::Ounce)), Value::new(1.0, Unit::Pound).convert(Some(Unit::Ounce)));
+    assert_eq!(Some(Value::new(14.0, Unit::Pound)), Value::new(1.0, Unit::Stone).convert(Some(Unit::Pound)));
+    assert_eq!(Some(Value::new(2.20462262185, Unit::Pound)), Value::new(1.0, Unit::Kilogram).convert(Some(Unit::Pound)));
+    assert_eq!(None, Value::new(1.0, Unit::Cup).convert(Some(Unit::Gram)));
+    assert_eq!(Some(Value::new(3.10685596119, Unit::Mile)), Value::new(5.0, Unit::Kilometer).convert(Some(Unit::Mile)));
+    assert_eq!(Some(Value::new(30.48, Unit::Centimeter)), Value::new(1.0, Unit::Foot).convert(Some(Unit::Centimeter)));
+    assert_eq!(Some(Value::new(36.0, Unit::Inch)), Value::new(1.0, Unit::Yard).convert(Some(Unit::Inch)));
+    assert_eq!(Some(Value::new(1.852, Unit::Kilometer)), Value::new(1.0, Unit::NauticalMile).convert(Some(Unit::Kilometer)));
+    assert_eq!(None, Value::new(1.0, Unit::Meter).convert(Some(Unit::Liter)));
+    assert_eq!(Some(Value::new(176.666666667, Unit::Celsius)), Value::new(350.0, Unit::Fahrenheit).convert(Some(Unit::Celsius)));
+    assert_eq!(Some(Value::new(212.0, Unit::Fahrenheit)), Value::new(100.0, Unit::Celsius).convert(Some(Unit::Fahrenheit)));
+    assert_eq!(Some(Value::new(-40.0, Unit::Fahrenheit)), Value::new(-40.0, Unit::Celsius).convert(Some(Unit::Fahrenheit)));
+    assert_eq!(Some(Value::new(273.15, Unit::Kelvin)), Value::new(0.0, Unit::Celsius).convert(Some(Unit::Kelvin)));
+    assert_eq!(Some(Value::new(-459.67, Unit::Fahrenheit)), Value::new(0.0, Unit::Kelvin).convert(Some(Unit::Fahrenheit)));
+    assert_eq!(Some(Value::new(5.0, Unit::Celsius)), Value::new(9.0, Unit::Fahrenheit).interval(Unit::Celsius));
+    assert_eq!(None, Value::new(1.0, Unit::Celsius).convert(Some(Unit::Meter)));
+    
+    // every conversion round-trips
+    for a in Unit::ALL {
+      for b in Unit::ALL {
+        if let Some(v) = Value::new(1.0, a).convert(Some(b)) {
+          assert!((v.convert(Some(a)).unwrap().value() - 1.0).abs() < 1e-9);
+        }
+      }
+    }
+  }
+  
+  #[test]
+  fn scale() {
+    assert_eq!(Some(Unit::Tablespoon), Unit::Teaspoon.up());
+    assert_eq!(Some(Unit::Gallon), Unit::Quart.up());
+    assert_eq!(None, Unit::Gallon.up());
+    assert_eq!(Some(Unit::Centiliter), Unit::Milliliter.up());
+    assert_eq!(None, Unit::Liter.up());
+    assert_eq!(Some(Unit::Kilogram), Unit::Gram.up());
+    assert_eq!(Some(Unit::Pound), Unit::Ounce.up());
+    assert_eq!(Some(Unit::Ton), Unit::Pound.up());
+    assert_eq!(Some(Unit::Tonne), Unit::Kilogram.up());
+    
+    assert_eq!(Some(Unit::Foot), Unit::Inch.up());
+    assert_eq!(None, Unit::Mile.up()); // nautical miles are a separate system
+    assert_eq!(None, Unit::Celsius.up());
+    assert_eq!(Some(Unit::Minute), Unit::Hour.down());
+    assert_eq!(None, Unit::Second.down());
+    assert_eq!(None, Unit::Acre.up());
+    
+    // every unit is on the ladder of its dimension and system, if there is one
+    for unit in Unit::ALL {
+      let same: Vec<Unit> = Unit::ALL.iter().cloned().filter(|u| {
+        u.dimension() == unit.dimension() && u.system() == unit.system()
+      }).collect();
+      let ladder = unit.ladder();
+      if unit.dimension() == Dimension::Temperature || same.len() == 1 {
+        assert!(ladder.is_empty(), "{:?}", unit);
+      }else{
+        assert_eq!(same.len(), ladder.len(), "{:?}", unit);
+        assert!(same.iter().all(|u| { ladder.contains(u) }), "{:?}", unit);
+        assert!(ladder.windows(2).all(|w| { w[0].factor() < w[1].factor() }), "{:?}", unit);
+      }
+    }
   }
   
   #[test]
   fn operations() {
     assert_eq!(Value::raw(10.0), Value::raw(5.0) * Value::raw(2.0));
     
-    assert_eq!(Value::new(10.0, Unit::Teaspoon), Value::new(5.0, Unit::Teaspoon) * Value::new(2.0, Unit::Teaspoon));
+    let sq = |u: Unit| { Some(Compound::new(vec![(u, 2)])) };
+    assert_eq!(Value::option(10.0, sq(Unit::Teaspoon)), Value::new(5.0, Unit::Teaspoon) * Value::new(2.0, Unit::Teaspoon));
     assert_eq!(Value::new(10.0, Unit::Teaspoon), Value::new(5.0, Unit::Teaspoon) * Value::raw(2.0));
     assert_eq!(Value::new(10.0, Unit::Teaspoon), Value::raw(2.0) * Value::new(5.0, Unit::Teaspoon));
-    assert_eq!(Value::new(20.0, Unit::Tablespoon), Value::new(30.0, Unit::Teaspoon) * Value::new(2.0, Unit::Tablespoon));
+    assert_eq!(Value::option(180.0, sq(Unit::Teaspoon)), Value::new(30.0, Unit::Teaspoon) * Value::new(2.0, Unit::Tablespoon));
+    assert_eq!(Value::option(0.6, sq(Unit::Meter)), Value::new(2.0, Unit::Meter) * Value::new(30.0, Unit::Centimeter));
+    assert_eq!(Value::option(50.0, Compound::from("km/h")), Value::new(100.0, Unit::Kilometer) / Value::new(2.0, Unit::Hour));
+    assert_eq!(Value::new(5.0, Unit::Kilometer), Value::new(10.0, Unit::Kilometer) / Value::raw(2.0));
+    assert_eq!(Value::raw(2.0), Value::new(1.0, Unit::Kilogram) / Value::new(500.0, Unit::Gram));
+    assert_eq!(Value::new(3.0, Unit::Kilometer), Value::option(1.5, Compound::from("km/h")) * Value::new(2.0, Unit::Hour));
+    
+    assert_eq!(Some(Value::raw(8.0)), Value::raw(2.0).pow(Value::raw(3.0)));
+    assert_eq!(Some(Value::raw(0.5)), Value::raw(2.0).pow(Value::raw(-1.0)));
+    assert_eq!(Some(Value::new(2.0, Unit::Cup)), Value::new(2.0, Unit::Cup).pow(Value::raw(1.0)));
+    assert_eq!(Some(Value::option(4.0, sq(Unit::Cup))), Value::new(2.0, Unit::Cup).pow(Value::raw(2.0)));
+    assert_eq!(Some(Value::new(3.0, Unit::Meter)), Value::option(9.0, sq(Unit::Meter)).pow(Value::raw(0.5)));
+    assert_eq!(None, Value::new(2.0, Unit::Cup).pow(Value::raw(0.5)));
+    assert_eq!(None, Value::raw(2.0).pow(Value::new(2.0, Unit::Cup)));
+    
+    assert_eq!(Ok(Value::new(25.0, Unit::Celsius)), Value::new(20.0, Unit::Celsius) + Value::new(5.0, Unit::Celsius));
+    assert_eq!(Ok(Value::new(25.0, Unit::Celsius)), Value::new(20.0, Unit::Celsius) + Value::new(9.0, Unit::Fahrenheit));
+    assert_eq!(Ok(Value::new(68.0, Unit::Fahrenheit)), Value::new(77.0, Unit::Fahrenheit) - Value::new(5.0, Unit::Celsius));
+    assert_eq!(Ok(Value::new(10.0, Unit::Kelvin)), Value::new(30.0, Unit::Kelvin) - Value::new(20.0, Unit::Celsius));
+    assert_eq!(Ok(Value::new(22.0, Unit::Celsius)), Value::new(20.0, Unit::Celsius) + Value::raw(2.0));
+    
+    let mismatch = |l: &str, r: &str| { Err(error::Error::UnitMismatch(l.to_string(), r.to_string())) };
+    assert_eq!(mismatch("kg", "cup"), Value::new(3.0, Unit::Kilogram) + Value::new(2.0, Unit::Cup));
+    assert_eq!(mismatch("km", "h"), Value::new(3.0, Unit::Kilometer) - Value::new(2.0, Unit::Hour));
+    assert_eq!(mismatch("°C", "kg"), Value::new(3.0, Unit::Celsius) % Value::new(2.0, Unit::Kilogram));
+    assert_eq!(Ok(Value::new(5.0, Unit::Kilogram)), Value::new(3.0, Unit::Kilogram) + Value::raw(2.0));
+    
+    assert_eq!(Value::raw(-5.0), -Value::raw(5.0));
+    assert_eq!(Value::new(-5.0, Unit::Teaspoon), -Value::new(5.0, Unit::Teaspoon));
   }
 }
\ No newline at end of file
@@ -0,0 +1,311 @@
+use std::fmt;
+use std::ops;
+use std::cmp;
+
+use crate::rdl::unit;
+use crate::rdl::error;
+
+/// The result of evaluating an expression. Arithmetic is defined for
+/// numbers and percentages; booleans are produced by comparisons and
+/// consumed by logical operators and conditionals; strings can only be
+/// concatenated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Number(unit::Value),
+  Percent(f64), // 20% is Percent(20.0)
+  Boolean(bool),
+  String(String),
+}
+
+/// How a result is displayed: as it was computed, or packed into the
+/// largest unit it fills and split across smaller ones, as in '5 ft 3 in'.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Format {
+  Raw,
+  Packed,
+}
+
+impl Format {
+  pub fn from(name: &str) -> Option<Format> {
+    match name {
+      "raw"    => Some(Format::Raw),
+      "packed" => Some(Format::Packed),
+      _        => None,
+    }
+  }
+}
+
+impl Value {
+  pub fn raw(v: f64) -> Value {
+    Value::Number(unit::Value::raw(v))
+  }
+  
+  #[cfg(test)]
+  pub fn new(v: f64, u: unit::Unit) -> Value {
+    Value::Number(unit::Value::new(v, u))
+  }
+  
+  pub fn number(&self) -> Result<unit::Value, error::Error> {
+    match self {
+      Value::Number(v) => Ok(v.clone()),
+      _ => Err(error::Error::InvalidOperation(format!("Expected a number, got: {}", self))),
+    }
+  }
+  
+  /// Format this value for display. A number that was converted into a
+  /// unit is written in that unit, as it was asked for, and only packed
+  /// into any larger one when it wasn't.
+  pub fn format(&self, format: Format, converted: bool) -> String {
+    match (self, format) {
+      (Value::Number(v), Format::Packed) if converted => v.to_mixed(),
+      (Value::Number(v), Format::Packed) => v.to_packed(),
+      (Value::Number(v), Format::Raw) if converted => v.to_decimal(),
+      _ => self.to_string(),
+    }
+  }
+  
+  pub fn boolean(&self) -> Result<bool, error::Error> {
+    match self {
+      Value::Boolean(v) => Ok(*v),
+      _ => Err(error::Error::InvalidOperation(format!("Expected a boolean, got: {}", self))),
+    }
+  }
+  
+  /// Produce this number as a percentage of `whole`, as in
+  /// '30 as % of 120', converting `whole` to the unit of this number first.
+  pub fn percent_of(&self, whole: Value) -> Result<Value, error::Error> {
+    let (l, r) = (self.number()?, whole.number()?);
+    match r.convert_compound(l.unit()) {
+      Some(r) => Ok(Value::Percent(l.value() / r.value() * 100.0)),
+      None => Err(error::Error::InvalidOperation(format!("Cannot express {} as a percentage of {}", self, whole))),
+    }
+  }
+  
+  /// Apply this percentage to `base`, as in '20% of 150'.
+  pub fn of(&self, base: Value) -> Result<Value, error::Error> {
+    match (self, &base) {
+      (Value::Percent(p), Value::Number(n)) => Ok(Value::Number(n.clone() * unit::Value::raw(p / 100.0))),
+      (Value::Percent(p), Value::Percent(n)) => Ok(Value::Percent(n * p / 100.0)),
+      _ => Err(error::Error::InvalidOperation(format!("Cannot take {} of {}", self, base))),
+    }
+  }
+  
+  /// Find the whole that this number is the provided percentage of, as in
+  /// '30 is 25% of what'.
+  pub fn whole(&self, pct: Value) -> Result<Value, error::Error> {
+    match (self, &pct) {
+      (Value::Number(n), Value::Percent(p)) => Ok(Value::Number(n.clone() / unit::Value::raw(p / 100.0))),
+      _ => Err(error::Error::InvalidOperation(format!("{} is not a percentage", pct))),
+    }
+  }
+  
+  pub fn pow(&self, exp: Value) -> Result<Value, error::Error> {
+    match self.number()?.pow(exp.number()?) {
+      Some(res) => Ok(Value::Number(res)),
+      None => Err(error::Error::InvalidOperation(format!("Cannot raise {} to the power of {}", self, exp))),
+    }
+  }
+  
+  /// Order two values: strings by their text, and numbers by converting
+  /// the right operand to the unit of the left one first. Numbers that
+  /// cannot be converted are not comparable.
+  pub fn compare(&self, right: Value) -> Result<cmp::Ordering, error::Error> {
+    let (l, r) = match (self, &right) {
+      (Value::String(l), Value::String(r)) => return Ok(l.cmp(r)),
+      (Value::Percent(l), Value::Percent(r)) => (*l, *r),
+      _ => {
+        let (l, r) = (self.number()?, right.number()?);
+        match r.convert_compound(l.unit()) {
+          Some(r) => (l.value(), r.value()),
+          None => return Err(unit::mismatch(&l, &r)),
+        }
+      },
+    };
+    match l.partial_cmp(&r) {
+      Some(ord) => Ok(ord),
+      None => Err(error::Error::InvalidOperation(format!("Cannot compare {} with {}", self, right))),
+    }
+  }
+  
+  pub fn equals(&self, right: Value) -> Result<bool, error::Error> {
+    match (self, &right) {
+      (Value::Boolean(l), Value::Boolean(r)) => Ok(l == r),
+      _ => Ok(self.compare(right)? == cmp::Ordering::Equal),
+    }
+  }
+}
+
+impl From<unit::Value> for Value {
+  fn from(v: unit::Value) -> Self {
+    Value::Number(v)
+  }
+}
+
+fn invalid(left: &Value, right: &Value, op: &str) -> error::Error {
+  error::Error::InvalidOperation(format!("Cannot apply {} to {} and {}", op, left, right))
+}
+
+/// Adding or subtracting a percentage changes a number by that share of
+/// itself, so '150 + 10%' is 165. Percentages can also be added to one
+/// another, but not to a number on their right. Adding anything to a
+/// string concatenates its text.
+impl ops::Add<Value> for Value {
+  type Output = Result<Value, error::Error>;
+  
+  fn add(self, right: Value) -> Self::Output {
+    match (&self, &right) {
+      (Value::Number(l), Value::Number(r)) => Ok(Value::Number((l.clone() + r.clone())?)),
+      (Value::Number(l), Value::Percent(r)) => Ok(Value::Number(l.clone() * unit::Value::raw(1.0 + r / 100.0))),
+      (Value::Percent(l), Value::Percent(r)) => Ok(Value::Percent(l + r)),
+      (Value::String(_), _) | (_, Value::String(_)) => Ok(Value::String(format!("{}{}", self, right))),
+      _ => Err(invalid(&self, &right, "+")),
+    }
+  }
+}
+
+impl ops::Sub<Value> for Value {
+  type Output = Result<Value, error::Error>;
+  
+  fn sub(self, right: Value) -> Self::Output {
+    match (&self, &right) {
+      (Value::Number(l), Value::Number(r)) => Ok(Value::Number((l.clone() - r.clone())?)),
+      (Value::Number(l), Value::Percent(r)) => Ok(Value::Number(l.clone() * unit::Value::raw(1.0 - r / 100.0))),
+      (Value::Percent(l), Value::Percent(r)) => Ok(Value::Percent(l - r)),
+      _ => Err(invalid(&self, &right, "-")),
+    }
+  }
+}
+
+impl ops::Mul<Value> for Value {
+  type Output = Result<Value, error::Error>;
+  
+  fn mul(self, right: Value) -> Self::Output {
+    match (&self, &right) {
+      (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l.clone() * r.clone())),
+      (Value::Number(_), Value::Percent(_)) => right.of(self),
+      (Value::Percent(_), _) => self.of(right),
+      _ => Err(invalid(&self, &right, "*")),
+    }
+  }
+}
+
+impl ops::Div<Value> for Value {
+  type Output = Result<Value, error::Error>;
+  
+  fn div(self, right: Value) -> Self::Output {
+    match (&self, &right) {
+      (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l.clone() / r.clone())),
+      (Value::Number(_), Value::Percent(_)) => self.whole(right),
+      (Value::Percent(l), Value::Number(r)) if r.unit().is_none() => Ok(Value::Percent(l / r.value())),
+      (Value::Percent(l), Value::Percent(r)) => Ok(Value::raw(l / r)),
+      _ => Err(invalid(&self, &right, "/")),
+    }
+  }
+}
+
+impl ops::Rem<Value> for Value {
+  type Output = Result<Value, error::Error>;
+  
+  fn rem(self, right: Value) -> Self::Output {
+    match (&self, &right) {
+      (Value::Number(l), Value::Number(r)) => Ok(Value::Number((l.clone() % r.clone())?)),
+      _ => Err(invalid(&self, &right, "mod")),
+    }
+  }
+}
+
+impl ops::Neg for Value {
+  type Output = Result<Value, error::Error>;
+  
+  fn neg(self) -> Self::Output {
+    match self {
+      Value::Percent(v) => Ok(Value::Percent(-v)),
+      _ => Ok(Value::Number(-self.number()?)),
+    }
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Value::Number(v)  => v.fmt(f),
+      Value::Percent(v) => write!(f, "{}%", v),
+      Value::Boolean(v) => write!(f, "{}", v),
+      Value::String(v)  => write!(f, "{}", v),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  
+  #[test]
+  fn operations() {
+    assert_eq!(Ok(Value::raw(3.0)), Value::raw(1.0) + Value::raw(2.0));
+    assert_eq!(Ok(Value::new(32.0, unit::Unit::Tablespoon)), Value::new(1.0, unit::Unit::Cup) + Value::new(16.0, unit::Unit::Tablespoon));
+    assert_eq!(Ok(Value::raw(-1.0)), -Value::raw(1.0));
+    assert!((Value::raw(1.0) + Value::Boolean(true)).is_err());
+    assert!((Value::Boolean(true) * Value::Boolean(true)).is_err());
+    assert!((-Value::Boolean(true)).is_err());
+  }
+  
+  #[test]
+  fn percentages() {
+    assert_eq!(Ok(Value::raw(165.0)), Value::raw(150.0) + Value::Percent(10.0));
+    assert_eq!(Ok(Value::raw(127.5)), Value::raw(150.0) - Value::Percent(15.0));
+    assert_eq!(Ok(Value::new(1.5, unit::Unit::Kilogram)), Value::new(1.0, unit::Unit::Kilogram) + Value::Percent(50.0));
+    assert_eq!(Ok(Value::Percent(30.0)), Value::Percent(10.0) + Value::Percent(20.0));
+    assert!((Value::Percent(10.0) + Value::raw(150.0)).is_err());
+    
+    assert_eq!(Ok(Value::raw(30.0)), Value::raw(150.0) * Value::Percent(20.0));
+    assert_eq!(Ok(Value::raw(30.0)), Value::Percent(20.0) * Value::raw(150.0));
+    assert_eq!(Ok(Value::raw(30.0)), Value::Percent(20.0).of(Value::raw(150.0)));
+    assert_eq!(Ok(Value::new(30.0, unit::Unit::Gram)), Value::Percent(20.0).of(Value::new(150.0, unit::Unit::Gram)));
+    assert!(Value::raw(20.0).of(Value::raw(150.0)).is_err());
+    
+    assert_eq!(Ok(Value::Percent(25.0)), Value::raw(30.0).percent_of(Value::raw(120.0)));
+    assert_eq!(Ok(Value::Percent(25.0)), Value::new(250.0, unit::Unit::Gram).percent_of(Value::new(1.0, unit::Unit::Kilogram)));
+    assert!(Value::new(1.0, unit::Unit::Cup).percent_of(Value::new(1.0, unit::Unit::Gram)).is_err());
+    
+    assert_eq!(Ok(Value::raw(120.0)), Value::raw(30.0).whole(Value::Percent(25.0)));
+    assert_eq!(Ok(Value::raw(120.0)), Value::raw(30.0) / Value::Percent(25.0));
+    assert_eq!(Ok(Value::Percent(-5.0)), -Value::Percent(5.0));
+    assert!((Value::raw(4.0) % Value::Percent(3.0)).is_err());
+    assert_eq!("12.5%", Value::Percent(12.5).to_string());
+  }
+  
+  #[test]
+  fn strings() {
+    let s = |v: &str| { Value::String(v.to_string()) };
+    assert_eq!(Ok(s("Hello, world")), s("Hello, ") + s("world"));
+    assert_eq!(Ok(s("Total: 12.5")), s("Total: ") + Value::raw(12.5));
+    assert_eq!(Ok(s("2 kg each")), Value::new(2.0, unit::Unit::Kilogram) + s(" each"));
+    assert_eq!(Ok(s("up 10%")), s("up ") + Value::Percent(10.0));
+    assert!((s("a") - s("a")).is_err());
+    assert!((s("a") * Value::raw(2.0)).is_err());
+    assert!((-s("a")).is_err());
+    assert!(s("1").number().is_err());
+    
+    assert_eq!(Ok(true), s("a").equals(s("a")));
+    assert_eq!(Ok(cmp::Ordering::Less), s("a").compare(s("b")));
+    assert!(s("1").equals(Value::raw(1.0)).is_err());
+  }
+  
+  #[test]
+  fn comparisons() {
+    assert_eq!(Ok(cmp::Ordering::Less), Value::raw(1.0).compare(Value::raw(2.0)));
+    assert_eq!(Ok(cmp::Ordering::Greater), Value::new(1.0, unit::Unit::Kilogram).compare(Value::new(20.0, unit::Unit::Gram)));
+    assert_eq!(Ok(cmp::Ordering::Equal), Value::new(1.0, unit::Unit::Cup).compare(Value::new(16.0, unit::Unit::Tablespoon)));
+    assert_eq!(Ok(cmp::Ordering::Equal), Value::new(3.0, unit::Unit::Gram).compare(Value::raw(3.0)));
+    assert_eq!(Err(error::Error::UnitMismatch("cup".to_string(), "g".to_string())), Value::new(1.0, unit::Unit::Cup).compare(Value::new(1.0, unit::Unit::Gram)));
+    assert!(Value::Boolean(true).compare(Value::Boolean(false)).is_err());
+    assert_eq!(Ok(cmp::Ordering::Less), Value::Percent(5.0).compare(Value::Percent(10.0)));
+    assert!(Value::Percent(5.0).compare(Value::raw(10.0)).is_err());
+    
+    assert_eq!(Ok(true), Value::Boolean(true).equals(Value::Boolean(true)));
+    assert_eq!(Ok(false), Value::Boolean(true).equals(Value::Boolean(false)));
+    assert_eq!(Ok(true), Value::raw(2.0).equals(Value::raw(2.0)));
+    assert!(Value::raw(1.0).equals(Value::Boolean(true)).is_err());
+  }
+}